@@ -1,58 +1,350 @@
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
 use syn::{
-    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Field, Fields, GenericArgument,
-    Ident, Lit, Meta, NestedMeta, PathArguments, Result, Type,
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Field, Fields, FnArg, GenericArgument,
+    Ident, ItemFn, Lit, Meta, MetaList, MetaNameValue, NestedMeta, Pat, Path, PathArguments,
+    Result, ReturnType, Type, Visibility,
 };
 
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let name = input.ident;
-    let vis = input.vis;
+    match derive_builder(&input) {
+        Ok(expanded) => expanded,
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
+}
+
+#[proc_macro_attribute]
+pub fn builder(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = TokenStream::from(args);
+    let input = parse_macro_input!(input as ItemFn);
+
+    if !args.is_empty() {
+        return Error::new_spanned(args, "expected `#[builder]`")
+            .to_compile_error()
+            .into();
+    }
+
+    match fn_builder(input) {
+        Ok(expanded) => expanded,
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
+}
+
+fn derive_builder(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let vis = &input.vis;
 
     // Generate builder struct name.
     let builder_name = format_ident!("{}Builder", name);
 
-    // Inspect all struct fields.
-    let fields = convert_fields(&input.data);
-    if let Err(err) = fields {
-        return proc_macro::TokenStream::from(err.to_compile_error());
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Builder does not support generic structs",
+        ));
     }
-    let fields = fields.unwrap();
 
-    // Generate builder fields.
-    let builder_fields = fields.iter().map(|f| f.field_token());
-    // Generate setters for all the fields.
-    let builder_setters = fields.iter().map(|f| f.setter_token());
+    // Inspect struct level options and all struct fields.
+    let options = parse_struct_options(&input.attrs)?;
+    let fields = convert_fields(&input.data)?;
+
+    // Inline mode generates chainable `with_*` methods on the struct itself.
+    if options.inline {
+        let with_setters = fields.iter().map(|f| f.with_setter_token());
+        return Ok(quote! {
+            impl #name {
+                #(#with_setters)*
+            }
+        });
+    }
+
+    // Generate the builder struct with its setters.
+    let builder_struct = builder_struct(vis, &builder_name, &fields, &options, "build")?;
     // Generate struct constructor.
     let builder_constructor = fields.iter().map(|f| f.build_token());
-
-    let expanded = quote! {
-        #[derive(Default)]
-        #vis struct #builder_name {
-            #(#builder_fields)*
+    // Generate `builder()` function.
+    let builder_fn = if options.constructor {
+        let required = fields.iter().filter(|f| f.is_required());
+        let args = required.clone().map(|f| f.constructor_arg_token());
+        let inits = required.map(|f| f.constructor_init_token());
+        quote! {
+            pub fn builder(#(#args)*) -> #builder_name {
+                #builder_name {
+                    #(#inits)*
+                    ..<#builder_name as ::core::default::Default>::default()
+                }
+            }
         }
+    } else {
+        quote! {
+            pub fn builder() -> #builder_name {
+                <#builder_name as ::core::default::Default>::default()
+            }
+        }
+    };
 
-        impl #builder_name {
-            #(#builder_setters)*
+    Ok(quote! {
+        #builder_struct
 
-            pub fn build(&mut self) -> std::result::Result<#name, &'static str> {
-                Ok(#name {
+        impl #builder_name {
+            pub fn build(&mut self) -> ::core::result::Result<#name, ::std::string::String> {
+                ::core::result::Result::Ok(#name {
                     #(#builder_constructor)*
                 })
             }
         }
 
         impl #name {
-            pub fn builder() -> #builder_name {
-                #builder_name::default()
+            #builder_fn
+        }
+    })
+}
+
+fn fn_builder(mut input: ItemFn) -> Result<TokenStream> {
+    let sig = &input.sig;
+    let name = &sig.ident;
+    let vis = &input.vis;
+
+    // Generate builder struct name, e.g. `ConnectBuilder` for `connect`.
+    let builder_name = format_ident!("{}Builder", upper_camel_case(&name.unraw().to_string()));
+
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &sig.generics,
+            "#[builder] does not support generic functions",
+        ));
+    }
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(Error::new_spanned(
+            asyncness,
+            "#[builder] does not support async functions",
+        ));
+    }
+    if let Some(unsafety) = &sig.unsafety {
+        return Err(Error::new_spanned(
+            unsafety,
+            "#[builder] does not support unsafe functions, since call() is safe",
+        ));
+    }
+
+    // Turn the arguments into fields, so they can be inspected like struct fields.
+    let arg_fields = sig
+        .inputs
+        .iter()
+        .map(convert_fn_arg)
+        .collect::<Result<Vec<_>>>()?;
+    let fields = arg_fields
+        .iter()
+        .map(convert_field)
+        .collect::<Result<Vec<_>>>()?;
+
+    // A setter must not clash with the generated `new()` either.
+    for field in &fields {
+        if let Some(name) = field.setter_names().into_iter().find(|name| *name == "new") {
+            return Err(Error::new_spanned(
+                name,
+                "setter `new` conflicts with the generated `new()` function",
+            ));
+        }
+    }
+
+    // Generate the builder struct with its setters.
+    let options = StructOptions::default();
+    let builder_struct = builder_struct(vis, &builder_name, &fields, &options, "call")?;
+    // Generate the arguments of the call.
+    let call_args = fields.iter().map(|f| f.build_value_token());
+    let output = match &sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ty) => quote!(#ty),
+    };
+
+    let expanded = quote! {
+        #builder_struct
+
+        impl #builder_name {
+            pub fn new() -> Self {
+                <Self as ::core::default::Default>::default()
+            }
+
+            pub fn call(&mut self) -> ::core::result::Result<#output, ::std::string::String> {
+                ::core::result::Result::Ok(#name(#(#call_args),*))
+            }
+        }
+    };
+
+    // The `#[builder(...)]` attributes on the arguments are only meaningful to
+    // this macro, so they are stripped from the emitted function.
+    for arg in &mut input.sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            arg.attrs.retain(|attr| !attr.path.is_ident("builder"));
+        }
+    }
+
+    Ok(quote! {
+        #input
+
+        #expanded
+    })
+}
+
+// Convert a function argument into the equivalent struct field.
+fn convert_fn_arg(arg: &FnArg) -> Result<Field> {
+    let arg = match arg {
+        FnArg::Typed(arg) => arg,
+        FnArg::Receiver(receiver) => {
+            return Err(Error::new_spanned(
+                receiver,
+                "#[builder] does not support methods",
+            ))
+        }
+    };
+    let ident = match &*arg.pat {
+        Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => pat.ident.clone(),
+        pat => {
+            return Err(Error::new_spanned(
+                pat,
+                "#[builder] arguments must be plain identifiers",
+            ))
+        }
+    };
+    if let Type::Reference(ty) = &*arg.ty {
+        return Err(Error::new_spanned(
+            ty,
+            "#[builder] arguments must not be references, since the builder stores them",
+        ));
+    }
+    Ok(Field {
+        attrs: arg.attrs.clone(),
+        vis: Visibility::Inherited,
+        ident: Some(ident),
+        colon_token: Some(arg.colon_token),
+        ty: (*arg.ty).clone(),
+    })
+}
+
+// Convert a `snake_case` function name to `UpperCamelCase`.
+fn upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+// Generate the builder struct, its `Default` impl and the setters, plus a
+// `Debug` impl with `#[builder(debug)]`. `finish` is the name of the method
+// which consumes the builder.
+fn builder_struct(
+    vis: &Visibility,
+    builder_name: &Ident,
+    fields: &[BuilderField],
+    options: &StructOptions,
+    finish: &str,
+) -> Result<TokenStream> {
+    // A setter must not clash with the method which consumes the builder.
+    for field in fields {
+        if options.constructor && field.is_required() {
+            continue;
+        }
+        if let Some(name) = field
+            .setter_names()
+            .into_iter()
+            .find(|name| *name == finish)
+        {
+            return Err(Error::new_spanned(
+                name,
+                format!(
+                    "setter `{}` conflicts with the generated `{}()` method",
+                    finish, finish
+                ),
+            ));
+        }
+    }
+
+    // Generate builder fields.
+    let builder_fields = fields.iter().map(|f| f.field_token());
+    // Generate setters for all the fields which are not passed to the constructor.
+    let builder_setters = fields
+        .iter()
+        .filter(|f| !(options.constructor && f.is_required()))
+        .map(|f| f.setter_token());
+    // Generate the `Debug` impl. It is opt-in, since it needs every field type
+    // to implement `Debug`.
+    let builder_debug = if options.debug {
+        let builder_debug_fields = fields
+            .iter()
+            .map(|f| Ok(f.debug_token(parse_field_options(f.field())?.redact.is_some())))
+            .collect::<Result<Vec<_>>>()?;
+        let builder_name_str = builder_name.to_string();
+        let f = Ident::new("f", Span::mixed_site());
+        let debug = debug_ident();
+        quote! {
+            impl ::core::fmt::Debug for #builder_name {
+                fn fmt(&self, #f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    let mut #debug = #f.debug_struct(#builder_name_str);
+                    #(#builder_debug_fields)*
+                    #debug.finish()
+                }
             }
         }
+    } else {
+        // Without a `Debug` impl there is nothing for `redact` to hide from.
+        for field in fields {
+            if let Some(redact) = parse_field_options(field.field())?.redact {
+                return Err(Error::new_spanned(
+                    redact,
+                    "`redact` can only be used together with `#[builder(debug)]`",
+                ));
+            }
+        }
+        quote! {}
     };
+    // Generate initial values of the builder fields.
+    let builder_defaults = fields.iter().map(|f| f.default_token());
+
+    Ok(quote! {
+        #vis struct #builder_name {
+            #(#builder_fields)*
+        }
+
+        impl ::core::default::Default for #builder_name {
+            fn default() -> Self {
+                #builder_name {
+                    #(#builder_defaults)*
+                }
+            }
+        }
+
+        impl #builder_name {
+            #(#builder_setters)*
+        }
+
+        #builder_debug
+    })
+}
 
-    proc_macro::TokenStream::from(expanded)
+#[derive(Default)]
+struct StructOptions {
+    // Required fields are passed to `builder()` instead of having setters.
+    pub constructor: bool,
+    // Generate `with_*` methods on the struct instead of a builder.
+    pub inline: bool,
+    // Implement `Debug` for the builder.
+    pub debug: bool,
 }
 
 struct OptionalBuilderField<'a> {
@@ -64,15 +356,116 @@ struct RepeatedBuilderField<'a> {
     pub field: &'a Field,
     pub ty: &'a Type,
     pub each_name: Ident,
+    pub all_name: Option<Ident>,
+    // The field is an `Option<Vec<T>>` which stays `None` until the first push.
+    pub optional: bool,
+}
+
+impl<'a> RepeatedBuilderField<'a> {
+    // Name of the setter replacing the whole collection, or `None` when it
+    // would collide with the setter pushing a single item.
+    pub fn all_name(&self) -> Option<&Ident> {
+        match &self.all_name {
+            Some(all_name) => Some(all_name),
+            None => self
+                .field
+                .ident
+                .as_ref()
+                .filter(|name| **name != self.each_name),
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // self.args.push(arg);
+    // self.args.get_or_insert_with(Vec::new).push(arg);
+    // ```
+    pub fn push_token(&self) -> TokenStream {
+        let name = &self.field.ident;
+        let each_name = &self.each_name;
+        if self.optional {
+            quote! {
+                self.#name.get_or_insert_with(::std::vec::Vec::new).push(#each_name);
+            }
+        } else {
+            quote! {
+                self.#name.push(#each_name);
+            }
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // args: Vec<String>
+    // ```
+    pub fn all_arg_token(&self, all_name: &Ident) -> TokenStream {
+        let ty = &self.ty;
+        quote! {
+            #all_name: ::std::vec::Vec<#ty>
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // self.args = args;
+    // self.args = Some(args);
+    // ```
+    pub fn assign_token(&self, all_name: &Ident) -> TokenStream {
+        let name = &self.field.ident;
+        if self.optional {
+            quote! {
+                self.#name = ::core::option::Option::Some(#all_name);
+            }
+        } else {
+            quote! {
+                self.#name = #all_name;
+            }
+        }
+    }
+}
+
+struct SubBuilderField<'a> {
+    pub field: &'a Field,
+    pub builder_ty: Type,
 }
 
 enum BuilderField<'a> {
     AllAtOnce(&'a Field),
+    Defaulted(&'a Field),
+    Skipped(&'a Field),
     Optional(OptionalBuilderField<'a>),
     Repeated(RepeatedBuilderField<'a>),
+    SubBuilder(SubBuilderField<'a>),
 }
 
 impl<'a> BuilderField<'a> {
+    pub fn field(&self) -> &'a Field {
+        match self {
+            Self::AllAtOnce(field) | Self::Defaulted(field) | Self::Skipped(field) => field,
+            Self::Optional(field) => field.field,
+            Self::Repeated(field) => field.field,
+            Self::SubBuilder(field) => field.field,
+        }
+    }
+
+    // Names of the methods generated by `setter_token`.
+    pub fn setter_names(&self) -> Vec<&Ident> {
+        match self {
+            Self::Repeated(field) => {
+                let mut names = vec![&field.each_name];
+                names.extend(field.all_name());
+                names
+            }
+            Self::Skipped(_) => vec![],
+            _ => self.field().ident.iter().collect(),
+        }
+    }
+
+    // Whether the field must be set before `build()` succeeds.
+    pub fn is_required(&self) -> bool {
+        matches!(self, Self::AllAtOnce(_))
+    }
+
     // Generated code looks like this:
     // ```rust
     // executable: Option<String>,
@@ -81,27 +474,69 @@ impl<'a> BuilderField<'a> {
     // ```
     pub fn field_token(&self) -> TokenStream {
         match self {
-            Self::AllAtOnce(field) => {
+            Self::AllAtOnce(field) | Self::Defaulted(field) => {
                 let name = &field.ident;
                 let ty = &field.ty;
                 quote! {
-                    #name: std::option::Option<#ty>,
+                    #name: ::core::option::Option<#ty>,
                 }
             }
             Self::Optional(field) => {
                 let name = &field.field.ident;
                 let ty = &field.ty;
                 quote! {
-                    #name: std::option::Option<#ty>,
+                    #name: ::core::option::Option<#ty>,
                 }
             }
             Self::Repeated(field) => {
                 let name = &field.field.ident;
                 let ty = &field.ty;
+                if field.optional {
+                    quote! {
+                        #name: ::core::option::Option<::std::vec::Vec<#ty>>,
+                    }
+                } else {
+                    quote! {
+                        #name: ::std::vec::Vec<#ty>,
+                    }
+                }
+            }
+            Self::SubBuilder(field) => {
+                let name = &field.field.ident;
+                let builder_ty = &field.builder_ty;
+                quote! {
+                    #name: #builder_ty,
+                }
+            }
+            Self::Skipped(_) => quote! {},
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // executable: None,
+    // args: Vec::new(),
+    // tls: TlsBuilder::default(),
+    // ```
+    pub fn default_token(&self) -> TokenStream {
+        let name = &self.field().ident;
+        match self {
+            Self::AllAtOnce(_) | Self::Defaulted(_) | Self::Optional(_) => quote! {
+                #name: ::core::option::Option::None,
+            },
+            Self::Repeated(field) if field.optional => quote! {
+                #name: ::core::option::Option::None,
+            },
+            Self::Repeated(_) => quote! {
+                #name: ::std::vec::Vec::new(),
+            },
+            Self::SubBuilder(field) => {
+                let builder_ty = &field.builder_ty;
                 quote! {
-                    #name: std::vec::Vec<#ty>,
+                    #name: <#builder_ty as ::core::default::Default>::default(),
                 }
             }
+            Self::Skipped(_) => quote! {},
         }
     }
 
@@ -123,15 +558,18 @@ impl<'a> BuilderField<'a> {
     //     self.args = args;
     //     self
     // }
+    // pub fn tls(&mut self) -> &mut TlsBuilder {
+    //     &mut self.tls
+    // }
     // ```
     pub fn setter_token(&self) -> TokenStream {
         match self {
-            Self::AllAtOnce(field) => {
+            Self::AllAtOnce(field) | Self::Defaulted(field) => {
                 let name = &field.ident;
                 let ty = &field.ty;
                 quote! {
                     pub fn #name(&mut self, #name: #ty) -> &mut Self {
-                        self.#name = Some(#name);
+                        self.#name = ::core::option::Option::Some(#name);
                         self
                     }
                 }
@@ -141,133 +579,629 @@ impl<'a> BuilderField<'a> {
                 let ty = &field.ty;
                 quote! {
                     pub fn #name(&mut self, #name: #ty) -> &mut Self {
-                        self.#name = Some(#name);
+                        self.#name = ::core::option::Option::Some(#name);
                         self
                     }
                 }
             }
             Self::Repeated(field) => {
-                let field_name = field.field.ident.as_ref();
-                let each_name = &field.each_name;
-                let once_setter = if field_name.unwrap() == each_name {
-                    quote! {}
-                } else {
-                    let filed_ty = &field.field.ty;
+                let once_setter = field.all_name().map(|all_name| {
+                    let arg = field.all_arg_token(all_name);
+                    let assign = field.assign_token(all_name);
                     quote! {
-                        pub fn #field_name(&mut self, #field_name: #filed_ty) -> &mut Self {
-                            self.#field_name = #field_name;
+                        pub fn #all_name(&mut self, #arg) -> &mut Self {
+                            #assign
                             self
                         }
                     }
-                };
+                });
 
+                let each_name = &field.each_name;
                 let ty = &field.ty;
+                let push = field.push_token();
                 quote! {
                     #once_setter
 
                     pub fn #each_name(&mut self, #each_name: #ty) -> &mut Self {
-                        self.#field_name.push(#each_name);
+                        #push
                         self
                     }
                 }
             }
+            Self::SubBuilder(field) => {
+                let name = &field.field.ident;
+                let builder_ty = &field.builder_ty;
+                quote! {
+                    pub fn #name(&mut self) -> &mut #builder_ty {
+                        &mut self.#name
+                    }
+                }
+            }
+            Self::Skipped(_) => quote! {},
         }
     }
 
     // Generated code looks like this:
     // ```rust
-    // executable: self.executable.take().ok_or("Missing field executable")?,
-    // current_dir: self.args.take(),
-    // args: std::mem::replace(&mut self.args, vec![]),
+    // pub fn with_executable(mut self, executable: String) -> Self {
+    //     self.executable = executable;
+    //     self
+    // }
+    // pub fn with_current_dir(mut self, current_dir: String) -> Self {
+    //     self.current_dir = Some(current_dir);
+    //     self
+    // }
+    // pub fn with_arg(mut self, arg: String) -> Self {
+    //     self.args.push(arg);
+    //     self
+    // }
+    // pub fn with_args(mut self, args: Vec<String>) -> Self {
+    //     self.args = args;
+    //     self
+    // }
     // ```
-    pub fn build_token(&self) -> TokenStream {
+    pub fn with_setter_token(&self) -> TokenStream {
         match self {
-            Self::AllAtOnce(field) => {
-                let name = field.ident.as_ref();
-                let message = format!("Missing required field {}", name.unwrap().to_string());
+            Self::AllAtOnce(_) | Self::Defaulted(_) | Self::SubBuilder(_) => {
+                let field = self.field();
+                let name = &field.ident;
+                let ty = &field.ty;
+                let with_name = format_ident!("with_{}", name.as_ref().unwrap());
                 quote! {
-                    #name: self.#name.take().ok_or(#message)?,
+                    pub fn #with_name(mut self, #name: #ty) -> Self {
+                        self.#name = #name;
+                        self
+                    }
                 }
             }
             Self::Optional(field) => {
                 let name = &field.field.ident;
+                let ty = &field.ty;
+                let with_name = format_ident!("with_{}", name.as_ref().unwrap());
                 quote! {
-                    #name: self.#name.take(),
+                    pub fn #with_name(mut self, #name: #ty) -> Self {
+                        self.#name = ::core::option::Option::Some(#name);
+                        self
+                    }
                 }
             }
             Self::Repeated(field) => {
-                let name = &field.field.ident;
+                let once_setter = field.all_name().map(|all_name| {
+                    let with_name = format_ident!("with_{}", all_name);
+                    let arg = field.all_arg_token(all_name);
+                    let assign = field.assign_token(all_name);
+                    quote! {
+                        pub fn #with_name(mut self, #arg) -> Self {
+                            #assign
+                            self
+                        }
+                    }
+                });
+
+                let each_name = &field.each_name;
+                let ty = &field.ty;
+                let with_each_name = format_ident!("with_{}", each_name);
+                let push = field.push_token();
                 quote! {
-                    #name: std::mem::replace(&mut self.#name, vec![]),
+                    #once_setter
+
+                    pub fn #with_each_name(mut self, #each_name: #ty) -> Self {
+                        #push
+                        self
+                    }
                 }
             }
+            Self::Skipped(_) => quote! {},
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // executable: String,
+    // ```
+    pub fn constructor_arg_token(&self) -> TokenStream {
+        let field = self.field();
+        let name = &field.ident;
+        let ty = &field.ty;
+        quote! {
+            #name: #ty,
         }
     }
+
+    // Generated code looks like this:
+    // ```rust
+    // executable: Some(executable),
+    // ```
+    pub fn constructor_init_token(&self) -> TokenStream {
+        let name = &self.field().ident;
+        quote! {
+            #name: ::core::option::Option::Some(#name),
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // match &self.executable {
+    //     Some(value) => debug.field("executable", value),
+    //     None => debug.field("executable", &format_args!("<missing>")),
+    // };
+    // debug.field("current_dir", &self.current_dir);
+    // debug.field("args", &self.args);
+    // ```
+    //
+    // Unset fields with a default value print as `<default>` and values of
+    // redacted fields are replaced with `<redacted>`.
+    pub fn debug_token(&self, redact: bool) -> TokenStream {
+        let name = &self.field().ident;
+        let name_str = name.as_ref().unwrap().unraw().to_string();
+        let debug = debug_ident();
+        let value = Ident::new("value", Span::mixed_site());
+        match self {
+            Self::AllAtOnce(_) | Self::Defaulted(_) => {
+                let unset = if let Self::Defaulted(_) = self {
+                    "<default>"
+                } else {
+                    "<missing>"
+                };
+                let (pattern, shown) = if redact {
+                    (quote!(_), quote!(&::core::format_args!("<redacted>")))
+                } else {
+                    (quote!(#value), quote!(#value))
+                };
+                quote! {
+                    match &self.#name {
+                        ::core::option::Option::Some(#pattern) => #debug.field(#name_str, #shown),
+                        ::core::option::Option::None => {
+                            #debug.field(#name_str, &::core::format_args!(#unset))
+                        }
+                    };
+                }
+            }
+            Self::Optional(_) if redact => quote! {
+                match &self.#name {
+                    ::core::option::Option::Some(_) => {
+                        #debug.field(#name_str, &::core::format_args!("Some(<redacted>)"))
+                    }
+                    ::core::option::Option::None => #debug.field(#name_str, &::core::format_args!("None")),
+                };
+            },
+            Self::Repeated(_) | Self::SubBuilder(_) if redact => quote! {
+                #debug.field(#name_str, &::core::format_args!("<redacted>"));
+            },
+            // The child builder only implements `Debug` if it has
+            // `#[builder(debug)]` too, so a missing impl is reported at the
+            // field.
+            Self::SubBuilder(field) => {
+                let value = quote_spanned!(field.field.ty.span()=> &self.#name);
+                quote! {
+                    #debug.field(#name_str, #value);
+                }
+            }
+            Self::Optional(_) | Self::Repeated(_) => quote! {
+                #debug.field(#name_str, &self.#name);
+            },
+            Self::Skipped(_) => quote! {},
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // executable: match self.executable.take() {
+    //     Some(value) => value,
+    //     None => return Err(String::from("Missing required field executable")),
+    // },
+    // ```
+    pub fn build_token(&self) -> TokenStream {
+        let name = &self.field().ident;
+        let value = self.build_value_token();
+        quote! {
+            #name: #value,
+        }
+    }
+
+    // Generated code looks like this:
+    // ```rust
+    // match self.executable.take() {
+    //     Some(value) => value,
+    //     None => return Err(String::from("Missing required field executable")),
+    // }
+    // match self.timeout.take() {
+    //     Some(value) => value,
+    //     None => Default::default(),
+    // }
+    // self.current_dir.take()
+    // std::mem::replace(&mut self.args, Vec::new())
+    // match self.tls.build() {
+    //     Ok(value) => value,
+    //     Err(err) => {
+    //         let path = err.strip_prefix("Missing required field ").unwrap_or(&err);
+    //         return Err(format!("Missing required field {}.{}", "tls", path));
+    //     }
+    // }
+    // Default::default()
+    // ```
+    pub fn build_value_token(&self) -> TokenStream {
+        let name = &self.field().ident;
+        let value = Ident::new("value", Span::mixed_site());
+        match self {
+            Self::AllAtOnce(_) => {
+                let message = format!("{}{}", MISSING_FIELD, name.as_ref().unwrap().unraw());
+                quote! {
+                    match self.#name.take() {
+                        ::core::option::Option::Some(#value) => #value,
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(<::std::string::String as ::core::convert::From<&str>>::from(#message));
+                        }
+                    }
+                }
+            }
+            Self::Defaulted(_) => quote! {
+                match self.#name.take() {
+                    ::core::option::Option::Some(#value) => #value,
+                    ::core::option::Option::None => ::core::default::Default::default(),
+                }
+            },
+            Self::Optional(_) => quote! {
+                self.#name.take()
+            },
+            Self::Repeated(field) if field.optional => quote! {
+                self.#name.take()
+            },
+            Self::Repeated(_) => quote! {
+                ::core::mem::replace(&mut self.#name, ::std::vec::Vec::new())
+            },
+            Self::SubBuilder(_) => {
+                let name_str = name.as_ref().unwrap().unraw().to_string();
+                let err = Ident::new("err", Span::mixed_site());
+                let path = Ident::new("path", Span::mixed_site());
+                let format = format!("{}{{}}.{{}}", MISSING_FIELD);
+                quote! {
+                    match self.#name.build() {
+                        ::core::result::Result::Ok(#value) => #value,
+                        ::core::result::Result::Err(#err) => {
+                            let #path = ::core::option::Option::unwrap_or(
+                                ::core::primitive::str::strip_prefix(&#err, #MISSING_FIELD),
+                                &#err,
+                            );
+                            return ::core::result::Result::Err(::std::format!(#format, #name_str, #path));
+                        }
+                    }
+                }
+            }
+            Self::Skipped(_) => quote! {
+                ::core::default::Default::default()
+            },
+        }
+    }
+}
+
+// Start of the error returned by `build()` when a required field is not set.
+// Errors from sub-builders are all of this form, so the field path is spliced
+// in after it, like "Missing required field server.tls.cert".
+const MISSING_FIELD: &str = "Missing required field ";
+
+// Name of the `DebugStruct` local in the builder's `Debug` impl.
+fn debug_ident() -> Ident {
+    Ident::new("debug", Span::mixed_site())
 }
 
+const UNSUPPORTED_DATA: &str = "Builder can only be derived for structs with named fields";
+
 // Convert all the fields in `data`.
 fn convert_fields<'a>(data: &'a Data) -> Result<Vec<BuilderField<'a>>> {
     match data {
         Data::Struct(data) => match &data.fields {
-            Fields::Named(fields) => fields.named.iter().map(|f| convert_field(f)).collect(),
-            _ => unimplemented!(),
+            Fields::Named(fields) => fields.named.iter().map(convert_field).collect(),
+            Fields::Unnamed(fields) => Err(Error::new_spanned(fields, UNSUPPORTED_DATA)),
+            Fields::Unit => Err(Error::new_spanned(data.struct_token, UNSUPPORTED_DATA)),
         },
-        _ => unimplemented!(),
+        Data::Enum(data) => Err(Error::new_spanned(data.enum_token, UNSUPPORTED_DATA)),
+        Data::Union(data) => Err(Error::new_spanned(data.union_token, UNSUPPORTED_DATA)),
     }
 }
 
 // Convert a `field`.
 fn convert_field<'a>(field: &'a Field) -> Result<BuilderField<'a>> {
-    let mut each_name = Option::<Ident>::default();
-    for attr in &field.attrs {
-        if let Ok(Meta::List(meta_list)) = attr.parse_meta() {
-            if meta_list.path.is_ident("builder") {
-                for nested in &meta_list.nested {
-                    if let NestedMeta::Meta(Meta::NameValue(name_value)) = nested {
-                        if name_value.path.is_ident("each") {
-                            if let Lit::Str(name) = &name_value.lit {
-                                each_name = Some(Ident::new(&name.value(), name.span()));
-                                break;
-                            }
-                        } else {
-                            let span = attr.path.span().join(attr.tokens.span()).unwrap();
-                            return Err(Error::new(span, r#"expected `builder(each = "...")`"#));
+    let FieldOptions {
+        each,
+        sub_builder,
+        skip,
+        default,
+        ..
+    } = parse_field_options(field)?;
+
+    if let Some(default) = default {
+        if skip.is_some() || each.is_some() || sub_builder {
+            return Err(Error::new_spanned(
+                default,
+                "`default` cannot be used together with `skip`, `each` or `sub_builder`",
+            ));
+        }
+        return Ok(BuilderField::Defaulted(field));
+    }
+
+    if let Some(skip) = skip {
+        if each.is_some() || sub_builder {
+            return Err(Error::new_spanned(
+                skip,
+                "`skip` cannot be used together with `each` or `sub_builder`",
+            ));
+        }
+        return Ok(BuilderField::Skipped(field));
+    }
+
+    if sub_builder {
+        if let Some(each) = &each {
+            return Err(Error::new_spanned(
+                &each.name,
+                "`each` cannot be used together with `sub_builder`",
+            ));
+        }
+        return Ok(BuilderField::SubBuilder(SubBuilderField {
+            field,
+            builder_ty: sub_builder_type(&field.ty)?,
+        }));
+    }
+
+    if let Some(EachOptions { name, all }) = each {
+        let (ty, optional) =
+            match generic_argument(&field.ty, "Vec") {
+                Some(ty) => (ty, false),
+                None => match generic_argument(&field.ty, "Option")
+                    .and_then(|ty| generic_argument(ty, "Vec"))
+                {
+                    Some(ty) => (ty, true),
+                    None => return Err(Error::new_spanned(
+                        name,
+                        "`each` can only be used on a field of type `Vec<T>` or `Option<Vec<T>>`",
+                    )),
+                },
+            };
+        return Ok(BuilderField::Repeated(RepeatedBuilderField {
+            field,
+            ty,
+            each_name: name,
+            all_name: all,
+            optional,
+        }));
+    }
+
+    if let Some(ty) = generic_argument(&field.ty, "Option") {
+        return Ok(BuilderField::Optional(OptionalBuilderField { field, ty }));
+    }
+    Ok(BuilderField::AllAtOnce(field))
+}
+
+// Returns `T` if `ty` is `#wrapper<T>`, e.g. `Option<T>` or `Vec<T>`. Fully
+// qualified paths like `::std::option::Option<T>` are recognized as well.
+fn generic_argument<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    if let Type::Path(path) = ty {
+        if path.qself.is_none() {
+            let segments = &path.path.segments;
+            let prefix: Vec<_> = segments.iter().rev().skip(1).map(|s| &s.ident).collect();
+            let module = wrapper.to_lowercase();
+            let qualified = match prefix.as_slice() {
+                [] => path.path.leading_colon.is_none(),
+                [parent, krate] => {
+                    *parent == &module && ["std", "core", "alloc"].iter().any(|k| krate == k)
+                }
+                _ => false,
+            };
+            let segment = segments.last().unwrap();
+            if qualified && segment.ident == wrapper {
+                if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+                    let args = &arguments.args;
+                    if args.len() == 1 {
+                        if let GenericArgument::Type(ty) = args.first().unwrap() {
+                            return Some(ty);
                         }
                     }
                 }
             }
         }
     }
+    None
+}
 
-    if let Type::Path(path) = &field.ty {
-        if let None = &path.qself {
-            let segments = &path.path.segments;
-            if segments.len() == 1 {
-                let segment = segments.first().unwrap();
-                if segment.ident == "Option" || segment.ident == "Vec" {
-                    if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
-                        let args = &arguments.args;
-                        if args.len() == 1 {
-                            let arg = args.first().unwrap();
-                            if let GenericArgument::Type(ty) = arg {
-                                if segment.ident == "Option" {
-                                    return Ok(BuilderField::Optional(OptionalBuilderField {
-                                        field,
-                                        ty,
-                                    }));
-                                } else if let Some(each_name) = each_name {
-                                    return Ok(BuilderField::Repeated(RepeatedBuilderField {
-                                        field,
-                                        each_name,
-                                        ty,
-                                    }));
-                                }
-                            }
-                        }
+// Returns the builder type of a field type which derives `Builder`, e.g.
+// `config::Tls` becomes `config::TlsBuilder`.
+fn sub_builder_type(ty: &Type) -> Result<Type> {
+    if let Type::Path(path) = ty {
+        if let Some(segment) = path.path.segments.last() {
+            if path.qself.is_none() && segment.arguments.is_empty() {
+                let mut path = path.clone();
+                let segment = path.path.segments.last_mut().unwrap();
+                segment.ident = format_ident!("{}Builder", segment.ident);
+                return Ok(Type::Path(path));
+            }
+        }
+    }
+    Err(Error::new_spanned(
+        ty,
+        "`sub_builder` requires a non-generic struct type deriving Builder",
+    ))
+}
+
+struct EachOptions {
+    pub name: Ident,
+    pub all: Option<Ident>,
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    pub each: Option<EachOptions>,
+    pub sub_builder: bool,
+    // Keep the value out of the builder's `Debug` output.
+    pub redact: Option<Path>,
+    // The field has no setter and is built from its `Default` value.
+    pub skip: Option<Path>,
+    // The field has a setter but falls back to its `Default` value.
+    pub default: Option<Path>,
+}
+
+const FIELD_OPTIONS_EXPECTED: &str = r#"expected `builder(each = "...")`"#;
+const EACH_OPTIONS_EXPECTED: &str = r#"expected `each(name = "...", all = "...")`"#;
+
+// Parse the string literal of a `key = "..."` attribute as an identifier.
+fn parse_ident_lit(name_value: &MetaNameValue) -> Result<Ident> {
+    match &name_value.lit {
+        Lit::Str(name) => name.parse(),
+        lit => Err(Error::new_spanned(lit, "expected string literal")),
+    }
+}
+
+// Parse the nested `each(name = "...", all = "...")` attribute.
+fn parse_each_options(meta_list: &MetaList) -> Result<EachOptions> {
+    let mut name = None;
+    let mut all = None;
+    for nested in &meta_list.nested {
+        let name_value = match nested {
+            NestedMeta::Meta(Meta::NameValue(name_value)) => name_value,
+            _ => return Err(Error::new_spanned(nested, EACH_OPTIONS_EXPECTED)),
+        };
+        let slot = if name_value.path.is_ident("name") {
+            &mut name
+        } else if name_value.path.is_ident("all") {
+            &mut all
+        } else {
+            return Err(Error::new_spanned(nested, EACH_OPTIONS_EXPECTED));
+        };
+        if slot.is_some() {
+            let key = name_value.path.get_ident().unwrap();
+            return Err(Error::new_spanned(
+                nested,
+                format!("duplicate `{}` attribute", key),
+            ));
+        }
+        *slot = Some(parse_ident_lit(name_value)?);
+    }
+
+    let name = name.ok_or_else(|| Error::new_spanned(meta_list, EACH_OPTIONS_EXPECTED))?;
+    if all.as_ref() == Some(&name) {
+        return Err(Error::new_spanned(
+            all,
+            "`all` must differ from `name` in `each(...)`",
+        ));
+    }
+    Ok(EachOptions { name, all })
+}
+
+// Parse field level `#[builder(...)]` attributes.
+fn parse_field_options(field: &Field) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in &field.attrs {
+        if !attr.path.is_ident("builder") {
+            continue;
+        }
+        let meta_list = match attr.parse_meta()? {
+            Meta::List(meta_list) => meta_list,
+            meta => return Err(Error::new_spanned(meta, FIELD_OPTIONS_EXPECTED)),
+        };
+        for nested in &meta_list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(name_value))
+                    if name_value.path.is_ident("each") =>
+                {
+                    if options.each.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `each` attribute"));
+                    }
+                    options.each = Some(EachOptions {
+                        name: parse_ident_lit(name_value)?,
+                        all: None,
+                    });
+                }
+                NestedMeta::Meta(Meta::List(each_list)) if each_list.path.is_ident("each") => {
+                    if options.each.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `each` attribute"));
+                    }
+                    options.each = Some(parse_each_options(each_list)?);
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("sub_builder") => {
+                    if options.sub_builder {
+                        return Err(Error::new_spanned(
+                            nested,
+                            "duplicate `sub_builder` attribute",
+                        ));
+                    }
+                    options.sub_builder = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                    if options.skip.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `skip` attribute"));
+                    }
+                    options.skip = Some(path.clone());
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                    if options.default.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `default` attribute"));
+                    }
+                    options.default = Some(path.clone());
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("redact") => {
+                    if options.redact.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `redact` attribute"));
+                    }
+                    options.redact = Some(path.clone());
+                }
+                _ => return Err(Error::new_spanned(meta_list, FIELD_OPTIONS_EXPECTED)),
+            }
+        }
+    }
+    Ok(options)
+}
+
+const STRUCT_OPTIONS_EXPECTED: &str =
+    "expected `builder(constructor)`, `builder(inline)` or `builder(debug)`";
+
+// Parse struct level `#[builder(...)]` attributes.
+fn parse_struct_options(attrs: &[Attribute]) -> Result<StructOptions> {
+    let mut options = StructOptions::default();
+    for attr in attrs {
+        if !attr.path.is_ident("builder") {
+            continue;
+        }
+        let meta_list = match attr.parse_meta()? {
+            Meta::List(meta_list) => meta_list,
+            meta => return Err(Error::new_spanned(meta, STRUCT_OPTIONS_EXPECTED)),
+        };
+        for nested in &meta_list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("constructor") => {
+                    if options.constructor {
+                        return Err(Error::new_spanned(
+                            nested,
+                            "duplicate `constructor` attribute",
+                        ));
+                    }
+                    options.constructor = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("inline") => {
+                    if options.inline {
+                        return Err(Error::new_spanned(nested, "duplicate `inline` attribute"));
+                    }
+                    options.inline = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("debug") => {
+                    if options.debug {
+                        return Err(Error::new_spanned(nested, "duplicate `debug` attribute"));
                     }
+                    options.debug = true;
                 }
+                _ => return Err(Error::new_spanned(nested, STRUCT_OPTIONS_EXPECTED)),
             }
         }
+        if options.constructor && options.inline {
+            return Err(Error::new_spanned(
+                meta_list,
+                "`constructor` and `inline` cannot be used together",
+            ));
+        }
+        if options.debug && options.inline {
+            return Err(Error::new_spanned(
+                meta_list,
+                "`debug` and `inline` cannot be used together, since `inline` generates no builder",
+            ));
+        }
     }
-    return Ok(BuilderField::AllAtOnce(field));
+    Ok(options)
 }
@@ -0,0 +1,41 @@
+// With `#[builder(constructor)]` on the struct, required fields become
+// positional arguments of `builder()` in declaration order. Only optional and
+// repeated fields keep their setters, so it is no longer possible to forget a
+// required field.
+//
+//     impl Command {
+//         pub fn builder(executable: String, env: Vec<String>) -> CommandBuilder {...}
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(constructor)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder("cargo".to_owned(), vec![])
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.env.is_empty());
+    assert!(command.current_dir.is_none());
+
+    let command = Command::builder("rustc".to_owned(), vec!["RUST_LOG=1".to_owned()])
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.env, vec!["RUST_LOG=1"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
@@ -0,0 +1,43 @@
+// With `#[builder(inline)]` no separate builder struct is generated. Instead
+// the struct gets chainable `with_*` methods which take and return `Self`, to
+// be used starting from its `Default` value. Repeated fields get a push-style
+// method named after `each` in addition to the whole-collection one.
+//
+//     impl Command {
+//         pub fn with_executable(mut self, executable: String) -> Self {...}
+//         pub fn with_arg(mut self, arg: String) -> Self {...}
+//         pub fn with_args(mut self, args: Vec<String>) -> Self {...}
+//         pub fn with_current_dir(mut self, current_dir: String) -> Self {...}
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder, Default)]
+#[builder(inline)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::default()
+        .with_executable("cargo".to_owned())
+        .with_arg("build".to_owned())
+        .with_arg("--release".to_owned())
+        .with_current_dir("..".to_owned());
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.env.is_empty());
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let command = command
+        .with_args(vec!["check".to_owned()])
+        .with_env(vec!["RUST_LOG=1".to_owned()]);
+
+    assert_eq!(command.args, vec!["check"]);
+    assert_eq!(command.env, vec!["RUST_LOG=1"]);
+}
@@ -0,0 +1,52 @@
+// A field whose type itself derives Builder can be marked with
+// `#[builder(sub_builder)]`. The parent builder then stores the child's
+// builder and hands out a mutable reference to it instead of a setter.
+//
+//     impl ServerBuilder {
+//         pub fn tls(&mut self) -> &mut TlsBuilder {...}
+//     }
+//
+// The parent's build() builds the child and prefixes any error with the name
+// of the field, so a missing field deep in a nested config is reported with
+// its full path.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    #[builder(sub_builder)]
+    server: Server,
+    verbose: Option<bool>,
+}
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Builder)]
+pub struct Tls {
+    cert: String,
+    key: Option<String>,
+}
+
+fn main() {
+    let mut builder = Config::builder();
+    builder.server().host("localhost".to_owned());
+    builder.server().tls().cert("cert.pem".to_owned());
+    let config = builder.build().unwrap();
+
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.tls.cert, "cert.pem");
+    assert!(config.server.tls.key.is_none());
+    assert!(config.verbose.is_none());
+
+    let mut builder = Config::builder();
+    builder.server().host("localhost".to_owned());
+    builder.server().tls().key("key.pem".to_owned());
+    let err = builder.build().err();
+
+    assert_eq!(err.as_deref(), Some("Missing required field server.tls.cert"));
+}
@@ -0,0 +1,15 @@
+// Each key may be given at most once per field, whether it is repeated within
+// one attribute or spread over several. The error points at the second
+// occurrence.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    #[builder(each = "argument")]
+    args: Vec<String>,
+}
+
+fn main() {}
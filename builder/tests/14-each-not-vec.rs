@@ -0,0 +1,14 @@
+// `each` generates a push-style setter, which only makes sense for a `Vec<T>`
+// or `Option<Vec<T>>` field. Using it on anything else is reported at the name
+// given to `each` rather than silently ignored.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(each = "exe")]
+    executable: String,
+    args: Vec<String>,
+}
+
+fn main() {}
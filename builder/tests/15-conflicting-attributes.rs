@@ -0,0 +1,30 @@
+// Keys which ask for incompatible code generation cannot be combined, neither
+// on a field nor on the struct.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Config {
+    #[builder(sub_builder, each = "server")]
+    server: Server,
+}
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(skip, each = "arg")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+pub struct Process {
+    #[builder(default, skip)]
+    current_dir: String,
+}
+
+#[derive(Builder)]
+#[builder(constructor, inline)]
+pub struct Server {
+    host: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Unknown struct level keys are rejected just like unknown field level keys.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(construct)]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
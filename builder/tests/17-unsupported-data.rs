@@ -0,0 +1,18 @@
+// Builder only knows how to set named fields. Enums, tuple structs and unit
+// structs are rejected with an error instead of a panic inside the macro.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub enum Command {
+    Cargo,
+    Rustc,
+}
+
+#[derive(Builder)]
+pub struct Executable(String);
+
+#[derive(Builder)]
+pub struct Nothing;
+
+fn main() {}
@@ -0,0 +1,12 @@
+// Generic structs are not supported. Rather than emitting a builder which
+// fails to compile in confusing ways, the derive points at the generics.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command<T> {
+    executable: T,
+    args: Vec<T>,
+}
+
+fn main() {}
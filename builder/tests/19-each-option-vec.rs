@@ -0,0 +1,54 @@
+// `each` also works on `Option<Vec<T>>` fields, which stay `None` until the
+// first item is pushed.
+//
+// The longer form `each(name = "...", all = "...")` names both setters
+// explicitly. This keeps the whole-collection setter available even when the
+// push-style setter would take the field's own name.
+//
+//     impl CommandBuilder {
+//         pub fn env(&mut self, env: String) -> &mut Self {...}
+//         pub fn set_env(&mut self, env: Vec<String>) -> &mut Self {...}
+//     }
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Option<Vec<String>>,
+    #[builder(each(name = "env", all = "set_env"))]
+    env: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .env("RUST_LOG=1".to_owned())
+        .build()
+        .unwrap();
+
+    assert!(command.args.is_none());
+    assert_eq!(command.env, vec!["RUST_LOG=1"]);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .env("RUST_LOG=1".to_owned())
+        .set_env(vec!["RUST_BACKTRACE=1".to_owned()])
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args.unwrap(), vec!["build", "--release"]);
+    assert_eq!(command.env, vec!["RUST_BACKTRACE=1"]);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["check".to_owned()])
+        .arg("--all".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args.unwrap(), vec!["check", "--all"]);
+}
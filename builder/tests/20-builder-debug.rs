@@ -0,0 +1,39 @@
+// With `#[builder(debug)]` the builder implements Debug, so that a partially
+// filled builder can be logged when build() fails. Required fields which are
+// not set yet print as `<missing>`, and fields marked `#[builder(redact)]`
+// never print their value.
+//
+// The impl is opt-in because it needs every field type to implement Debug.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(debug)]
+pub struct Login {
+    user: String,
+    #[builder(redact)]
+    password: String,
+    #[builder(redact)]
+    token: Option<String>,
+    #[builder(each = "scope")]
+    scopes: Vec<String>,
+    server: String,
+    port: Option<u16>,
+}
+
+fn main() {
+    let mut builder = Login::builder();
+    builder
+        .user("root".to_owned())
+        .password("hunter2".to_owned())
+        .token("secret".to_owned())
+        .scope("read".to_owned());
+
+    let debug = format!("{:?}", builder);
+    let expected = r#"LoginBuilder { user: "root", password: <redacted>, token: Some(<redacted>), scopes: ["read"], server: <missing>, port: None }"#;
+    assert_eq!(debug, expected);
+
+    let debug = format!("{:?}", Login::builder());
+    let expected = r#"LoginBuilder { user: <missing>, password: <missing>, token: None, scopes: [], server: <missing>, port: None }"#;
+    assert_eq!(debug, expected);
+}
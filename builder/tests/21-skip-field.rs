@@ -0,0 +1,38 @@
+// A field marked `#[builder(skip)]` gets no setter and no storage in the
+// builder; build() fills it in from its Default impl instead.
+//
+// The builder's own Default impl is written out field by field, so none of
+// the user's field types need to implement Default, only the skipped ones.
+
+use derive_builder::Builder;
+
+pub struct Handle {
+    fd: i32,
+}
+
+#[derive(Builder)]
+pub struct Process {
+    executable: String,
+    handle: Handle,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(skip)]
+    exit_code: Option<i32>,
+    #[builder(skip)]
+    restarts: u32,
+}
+
+fn main() {
+    let process = Process::builder()
+        .executable("cargo".to_owned())
+        .handle(Handle { fd: 3 })
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(process.executable, "cargo");
+    assert_eq!(process.handle.fd, 3);
+    assert_eq!(process.args, vec!["build"]);
+    assert_eq!(process.exit_code, None);
+    assert_eq!(process.restarts, 0);
+}
@@ -0,0 +1,108 @@
+// Like 09-redefined-prelude-types, but for everything else the generated code
+// refers to. Every path emitted by the macro is fully qualified, so neither a
+// local module called `std`, nor redefinitions of `Ok`, `Err`, `vec!` or
+// `format_args!`, nor a module without the implicit prelude can break it.
+// Nor can a trait in scope whose methods share a name with the ones the
+// generated code calls.
+
+#![allow(dead_code, non_upper_case_globals, unused_macros)]
+
+mod std {}
+
+type Result = ();
+type String = ();
+type Vec = ();
+struct Ok;
+struct Err;
+const value: () = ();
+const debug: () = ();
+
+macro_rules! vec {
+    ($($tt:tt)*) => {
+        compile_error!("vec! must not be used")
+    };
+}
+
+macro_rules! format_args {
+    ($($tt:tt)*) => {
+        compile_error!("format_args! must not be used")
+    };
+}
+
+mod no_prelude {
+    #![no_implicit_prelude]
+
+    use ::derive_builder::Builder;
+
+    pub trait Hijack {
+        fn strip_prefix(&self, _prefix: &str) -> ::std::option::Option<&str> {
+            ::std::option::Option::Some("hijacked")
+        }
+    }
+
+    impl Hijack for ::std::string::String {}
+
+    #[derive(Builder)]
+    #[builder(debug)]
+    pub struct Command {
+        pub executable: ::std::string::String,
+        #[builder(each = "arg")]
+        pub args: ::std::vec::Vec<::std::string::String>,
+        pub current_dir: ::std::option::Option<::std::string::String>,
+        #[builder(sub_builder)]
+        pub limits: Limits,
+    }
+
+    #[derive(Builder)]
+    #[builder(debug)]
+    pub struct Limits {
+        pub memory: u64,
+        #[builder(redact)]
+        pub cpu: ::std::option::Option<u32>,
+    }
+
+    #[derive(Builder)]
+    #[builder(constructor)]
+    pub struct Job {
+        pub name: ::std::string::String,
+        #[builder(skip)]
+        pub retries: u32,
+    }
+
+    #[derive(Builder, Default)]
+    #[builder(inline)]
+    pub struct Options {
+        pub verbose: ::std::option::Option<bool>,
+        #[builder(each = "feature")]
+        pub features: ::std::option::Option<::std::vec::Vec<::std::string::String>>,
+    }
+}
+
+fn main() {
+    let mut builder = no_prelude::Command::builder();
+    builder.executable(::std::string::String::from("cargo"));
+    builder.arg(::std::string::String::from("build"));
+    builder.limits().memory(1024);
+    let _ = ::std::format!("{:?}", builder);
+    let command = builder.build().unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build"]);
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.limits.memory, 1024);
+
+    let mut builder = no_prelude::Command::builder();
+    builder.executable(::std::string::String::from("cargo"));
+    let err = builder.build().err();
+    assert_eq!(err.as_deref(), Some("Missing required field limits.memory"));
+
+    let job = no_prelude::Job::builder(::std::string::String::from("test"))
+        .build()
+        .unwrap();
+    assert_eq!(job.name, "test");
+
+    let options = no_prelude::Options::default()
+        .with_verbose(true)
+        .with_feature(::std::string::String::from("std"));
+    assert_eq!(options.features.unwrap(), ["std"]);
+}
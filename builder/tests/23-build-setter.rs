@@ -0,0 +1,12 @@
+// A field called `build` would get a setter which clashes with the generated
+// build() method, so the derive reports it at the field name.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Job {
+    name: String,
+    build: String,
+}
+
+fn main() {}
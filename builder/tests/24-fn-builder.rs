@@ -0,0 +1,80 @@
+// The `#[builder]` attribute turns a function's parameter list into a builder.
+// For `fn connect(...)` it emits the function unchanged alongside a
+// `ConnectBuilder` with one setter per argument, whose call() invokes the
+// function once all required arguments are set.
+//
+// Arguments marked `#[builder(default)]` may be left unset, in which case
+// their type's Default value is passed. The same `default` key is also
+// accepted on fields of `#[derive(Builder)]` structs.
+//
+// Raw identifiers lose their `r#` in generated names and messages, so
+// `fn r#match` gets a `MatchBuilder`.
+
+use derive_builder::builder;
+use std::time::Duration;
+
+#[builder]
+fn connect(
+    host: String,
+    port: u16,
+    #[builder(default)] timeout: Duration,
+    #[builder(each = "option")] options: Vec<String>,
+    proxy: Option<String>,
+) -> String {
+    format!(
+        "{}:{} timeout={:?} options={:?} proxy={:?}",
+        host, port, timeout, options, proxy
+    )
+}
+
+#[builder]
+pub fn shutdown(force: bool) {
+    assert!(force);
+}
+
+#[builder]
+fn r#match(r#type: u8, pattern: String) -> bool {
+    pattern.len() == r#type as usize
+}
+
+fn main() {
+    let connection = ConnectBuilder::new()
+        .host("localhost".to_owned())
+        .port(8080)
+        .option("nodelay".to_owned())
+        .call()
+        .unwrap();
+    assert_eq!(
+        connection,
+        r#"localhost:8080 timeout=0ns options=["nodelay"] proxy=None"#,
+    );
+
+    let connection = ConnectBuilder::new()
+        .host("localhost".to_owned())
+        .port(8080)
+        .timeout(Duration::from_secs(1))
+        .proxy("socks5://proxy".to_owned())
+        .call()
+        .unwrap();
+    assert_eq!(
+        connection,
+        r#"localhost:8080 timeout=1s options=[] proxy=Some("socks5://proxy")"#,
+    );
+
+    let err = ConnectBuilder::new().host("localhost".to_owned()).call();
+    assert_eq!(err, Err("Missing required field port".to_owned()));
+
+    // The function itself is still callable directly.
+    let connection = connect("example.com".to_owned(), 443, Duration::ZERO, vec![], None);
+    assert!(connection.starts_with("example.com:443"));
+
+    ShutdownBuilder::new().force(true).call().unwrap();
+
+    let matched = MatchBuilder::new()
+        .r#type(2)
+        .pattern("ab".to_owned())
+        .call();
+    assert_eq!(matched, Ok(true));
+    let err = MatchBuilder::new().pattern("ab".to_owned()).call();
+    assert_eq!(err, Err("Missing required field type".to_owned()));
+}
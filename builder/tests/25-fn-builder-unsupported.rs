@@ -0,0 +1,37 @@
+// Function builders store every argument until call(), so arguments need to
+// be plain owned values. Methods, borrowed arguments and destructuring
+// patterns are rejected.
+//
+// call() is a plain safe function, so async and unsafe functions are rejected
+// too, and no argument may be named after the generated `new()` or `call()`.
+
+use derive_builder::builder;
+
+struct Server;
+
+impl Server {
+    #[builder]
+    fn start(&self, port: u16) {}
+}
+
+#[builder]
+fn greet(name: &str) {}
+
+#[builder]
+fn distance((x, y): (f64, f64)) -> f64 {
+    (x * x + y * y).sqrt()
+}
+
+#[builder]
+async fn fetch(url: String) {}
+
+#[builder]
+unsafe fn poke(address: usize, value: u8) {}
+
+#[builder]
+fn create(new: bool) {}
+
+#[builder]
+fn dial(#[builder(each = "call")] calls: Vec<String>) {}
+
+fn main() {}
@@ -0,0 +1,27 @@
+// Without `#[builder(debug)]` the builder has no Debug impl, so fields of any
+// type can be built, including types which don't implement Debug.
+
+use derive_builder::Builder;
+
+pub struct NoDebug {
+    id: u32,
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    handle: NoDebug,
+    fallback: Option<NoDebug>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .handle(NoDebug { id: 1 })
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.handle.id, 1);
+    assert!(command.fallback.is_none());
+}
@@ -0,0 +1,35 @@
+// A field marked `#[builder(default)]` keeps its setter, but may be left unset,
+// in which case build() fills it in from the type's Default value instead of
+// failing.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Server {
+    host: String,
+    #[builder(default)]
+    port: u16,
+    #[builder(default)]
+    workers: Vec<String>,
+}
+
+fn main() {
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 0);
+    assert!(server.workers.is_empty());
+
+    let server = Server::builder()
+        .host("localhost".to_owned())
+        .port(8080)
+        .workers(vec!["main".to_owned()])
+        .build()
+        .unwrap();
+    assert_eq!(server.port, 8080);
+    assert_eq!(server.workers, ["main"]);
+
+    assert!(Server::builder().port(80).build().is_err());
+}
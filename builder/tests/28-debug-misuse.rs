@@ -0,0 +1,37 @@
+// `#[builder(debug)]` is needed for anything that affects the builder's Debug
+// output, and it only applies to the builder of the struct it is written on:
+//
+//   - a sub-builder is shown through its own Debug impl, so the child struct
+//     needs `#[builder(debug)]` as well, and the error points at the field;
+//   - `#[builder(redact)]` has nothing to hide from without the Debug impl;
+//   - `#[builder(inline)]` generates no builder to implement Debug for.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(debug)]
+pub struct Server {
+    host: String,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Builder)]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Builder)]
+pub struct Login {
+    user: String,
+    #[builder(redact)]
+    password: String,
+}
+
+#[derive(Builder, Default)]
+#[builder(inline, debug)]
+pub struct Options {
+    verbose: Option<bool>,
+}
+
+fn main() {}
@@ -10,4 +10,23 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-constructor.rs");
+    t.pass("tests/11-inline.rs");
+    t.pass("tests/12-sub-builder.rs");
+    t.compile_fail("tests/13-duplicate-attribute.rs");
+    t.compile_fail("tests/14-each-not-vec.rs");
+    t.compile_fail("tests/15-conflicting-attributes.rs");
+    t.compile_fail("tests/16-unrecognized-struct-attribute.rs");
+    t.compile_fail("tests/17-unsupported-data.rs");
+    t.compile_fail("tests/18-generic-struct.rs");
+    t.pass("tests/19-each-option-vec.rs");
+    t.pass("tests/20-builder-debug.rs");
+    t.pass("tests/21-skip-field.rs");
+    t.pass("tests/22-redefined-result-types.rs");
+    t.compile_fail("tests/23-build-setter.rs");
+    t.pass("tests/24-fn-builder.rs");
+    t.compile_fail("tests/25-fn-builder-unsupported.rs");
+    t.pass("tests/26-non-debug-field.rs");
+    t.pass("tests/27-default-field.rs");
+    t.compile_fail("tests/28-debug-misuse.rs");
 }
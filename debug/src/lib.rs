@@ -1,36 +1,1883 @@
-use quote::quote;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Fields};
+use std::collections::HashSet;
 
-#[proc_macro_derive(CustomDebug)]
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::ext::IdentExt;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Field, Fields,
+    GenericArgument, Generics, Ident, Lit, LitStr, Meta, MetaNameValue, NestedMeta, Path,
+    PathArguments, Result, Token, Type, WherePredicate,
+};
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let name = input.ident;
-    let name_str = name.to_string();
+    match derive_debug(&input) {
+        Ok(expanded) => expanded,
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
+}
 
-    let recurse = match &input.data {
-        Data::Struct(DataStruct {
-            fields: Fields::Named(fields),
-            ..
-        }) => fields.named.iter().map(|f| {
-            let name = &f.ident;
-            let name_str = f.ident.as_ref().unwrap().to_string();
-            quote! {
-                .field(#name_str, &self.#name)
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match derive_custom_display(&input) {
+        Ok(expanded) => expanded,
+        Err(error) => error.to_compile_error(),
+    }
+    .into()
+}
+
+fn derive_debug(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+    let options = parse_struct_options(&input.attrs)?;
+
+    match &options.mode {
+        Some(Mode::Display) | Some(Mode::With(_)) => return derive_debug_forwarded(input, options),
+        Some(Mode::Transparent(transparent)) => {
+            let single_field = matches!(&input.data, Data::Struct(data) if data.fields.len() == 1);
+            if !single_field {
+                return Err(Error::new_spanned(
+                    transparent,
+                    "`transparent` can only be used on structs with exactly one field",
+                ));
             }
+        }
+        None => {}
+    }
+
+    let variants = match &input.data {
+        Data::Struct(data) => vec![Variant {
+            path: quote!(#name),
+            name: match &options.name {
+                Some(name) => name.value(),
+                None => name.unraw().to_string(),
+            },
+            fields: &data.fields,
+            rename_all: options.rename_all,
+            transparent: matches!(options.mode, Some(Mode::Transparent(_))),
+            skip_none: options.skip_none,
+            packed: is_packed(&input.attrs)?,
+        }],
+        Data::Enum(data) => {
+            if let Some(name) = &options.name {
+                return Err(Error::new_spanned(
+                    name,
+                    "`name` has no effect on an enum, use it on the variants instead",
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let variant_options = parse_variant_options(&variant.attrs)?;
+                    let variant_name = &variant.ident;
+                    Ok(Variant {
+                        path: quote!(#name::#variant_name),
+                        name: match (variant_options.name, options.rename_all) {
+                            (Some(name), _) => name.value(),
+                            (None, Some(rule)) => rule.apply(&variant_name.unraw().to_string()),
+                            (None, None) => variant_name.unraw().to_string(),
+                        },
+                        fields: &variant.fields,
+                        rename_all: variant_options.rename_all,
+                        transparent: false,
+                        skip_none: options.skip_none,
+                        packed: false,
+                    })
+                })
+                .collect::<Result<_>>()?
+        }
+        Data::Union(_) => return derive_debug_union(input, options),
+    };
+
+    // Bounds written by the caller take precedence over the inferred ones:
+    // `#[debug(bound = "...")]` on the type replaces all of them, and on a
+    // field replaces only those inferred from that field's type.
+    let mut predicates = vec![];
+    let mut bounded = vec![];
+    let mut arms = vec![];
+    let mut adapters = Adapters::default();
+    for variant in &variants {
+        let field_options = variant
+            .fields
+            .iter()
+            .map(parse_field_options)
+            .collect::<Result<Vec<_>>>()?;
+        if let Fields::Unnamed(_) = variant.fields {
+            if let Some(rename) = field_options
+                .iter()
+                .find_map(|options| options.rename.as_ref())
+            {
+                return Err(Error::new_spanned(
+                    rename,
+                    "`rename` can only be used on named fields",
+                ));
+            }
+        }
+        if variant.transparent {
+            let skipped = matches!(field_options[0].render, Some(Render::Skip));
+            if skipped || field_options[0].skip_if.is_some() {
+                return Err(Error::new_spanned(
+                    variant.fields,
+                    "the field of a `transparent` struct cannot be skipped",
+                ));
+            }
+        }
+        if variant.packed && options.bound.is_none() {
+            let params = type_params(&input.generics);
+            for (field, options) in variant.fields.iter().zip(&field_options) {
+                let shown = !matches!(options.render, Some(Render::Skip));
+                let ty = &field.ty;
+                if shown && mentions_param(ty.to_token_stream(), &params) {
+                    predicates.push(parse_quote!(#ty: ::core::marker::Copy));
+                }
+            }
+        }
+        if options.bound.is_none() {
+            for (field, options) in variant.fields.iter().zip(&field_options) {
+                match (&options.bound, &options.render) {
+                    (Some(bound), _) => predicates.extend(bound.iter().cloned()),
+                    (None, Some(Render::Radix { radix, element })) => {
+                        let ty = element.as_deref().unwrap_or(&field.ty);
+                        let format_trait = radix.format_trait();
+                        push_format_bounds(ty, &format_trait, &input.generics, &mut predicates);
+                    }
+                    (None, Some(Render::Format(_, traits))) => {
+                        for format_trait in traits {
+                            if format_trait == "Debug" {
+                                collect_bounded_types(&field.ty, &input.generics, &mut bounded);
+                            } else {
+                                push_format_bounds(
+                                    &field.ty,
+                                    format_trait,
+                                    &input.generics,
+                                    &mut predicates,
+                                );
+                            }
+                        }
+                    }
+                    (None, _) if options.uses_debug() => {
+                        collect_bounded_types(&field.ty, &input.generics, &mut bounded)
+                    }
+                    (None, _) => {}
+                }
+            }
+        }
+        for options in &field_options {
+            adapters.require(options);
+        }
+        arms.push(variant.debug_arm(&field_options));
+    }
+    if let Some(bound) = options.bound {
+        predicates.extend(bound);
+    }
+    let generics = add_trait_bounds(input.generics.clone(), &bounded, predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // An enum without variants can't be matched through a reference.
+    let body = if arms.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote! {
+            match self {
+                #(#arms)*
+            }
+        }
+    };
+
+    let f = formatter();
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, #f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #adapters
+                #body
+            }
+        }
+    })
+}
+
+// Helper types wrapping field values whose output the derive customizes,
+// declared inside the generated `fmt` when some field needs them.
+#[derive(Default)]
+struct Adapters {
+    // `DebugWith`, for `#[debug(with = "...")]`.
+    with: bool,
+    // `DebugItems`, for `#[debug(max_items = N)]` and `#[debug(max_len = N)]`
+    // on sequences.
+    items: bool,
+    // `DebugStr`, for `#[debug(max_len = N)]` on strings.
+    str: bool,
+    // `DebugEach`, for `#[debug(hex)]` and the like on sequences.
+    each: bool,
+}
+
+impl Adapters {
+    fn require(&mut self, options: &FieldOptions) {
+        match &options.render {
+            Some(Render::With(_)) => self.with = true,
+            Some(render) if render.is_hex_bytes() => self.with = true,
+            Some(Render::Radix {
+                element: Some(_), ..
+            }) => {
+                self.with = true;
+                self.each = true;
+            }
+            Some(Render::MaxItems(_)) | Some(Render::MaxLen { text: false, .. }) => {
+                self.items = true
+            }
+            Some(Render::MaxLen { text: true, .. }) => self.str = true,
+            _ => {}
+        }
+    }
+}
+
+impl ToTokens for Adapters {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if self.with {
+            tokens.extend(quote! {
+                struct DebugWith<'a, T: ?::core::marker::Sized>(
+                    &'a T,
+                    fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+                );
+
+                impl<'a, T: ?::core::marker::Sized> ::core::fmt::Debug for DebugWith<'a, T> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        (self.1)(self.0, f)
+                    }
+                }
+            });
+        }
+        if self.items {
+            // Shows at most `self.1` items of the iterator, followed by how
+            // many were left out.
+            tokens.extend(quote! {
+                struct DebugItems<I>(I, usize);
+
+                impl<I> ::core::fmt::Debug for DebugItems<I>
+                where
+                    I: ::core::iter::Iterator + ::core::clone::Clone,
+                    I::Item: ::core::fmt::Debug,
+                {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        let mut items = ::core::clone::Clone::clone(&self.0);
+                        let mut list = f.debug_list();
+                        list.entries(::core::iter::Iterator::take(&mut items, self.1));
+                        let more = ::core::iter::Iterator::count(items);
+                        if more > 0 {
+                            list.entry(&::core::format_args!("… ({} more)", more));
+                        }
+                        list.finish()
+                    }
+                }
+            });
+        }
+        if self.each {
+            // Shows each element of the slice with the function.
+            tokens.extend(quote! {
+                struct DebugEach<'a, T>(
+                    &'a [T],
+                    fn(&T, &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result,
+                );
+
+                impl<'a, T> ::core::fmt::Debug for DebugEach<'a, T> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        let format = self.1;
+                        f.debug_list()
+                            .entries(self.0.iter().map(|item| DebugWith(item, format)))
+                            .finish()
+                    }
+                }
+            });
+        }
+        if self.str {
+            // Shows at most `self.1` characters of the string, followed by how
+            // many were left out.
+            tokens.extend(quote! {
+                struct DebugStr<'a>(&'a str, usize);
+
+                impl<'a> ::core::fmt::Debug for DebugStr<'a> {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        match self.0.char_indices().nth(self.1) {
+                            ::core::option::Option::Some((end, _)) => {
+                                ::core::fmt::Debug::fmt(&self.0[..end], f).and_then(|()| {
+                                    let more = self.0[end..].chars().count();
+                                    f.write_fmt(::core::format_args!("… ({} more)", more))
+                                })
+                            }
+                            ::core::option::Option::None => ::core::fmt::Debug::fmt(self.0, f),
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+// Implement `Debug` for a type with `#[debug(display)]` by forwarding to its
+// `Display` impl, or with `#[debug(with = "...")]` by calling the function.
+// Unless given by `#[debug(bound = "...")]`, the only bound is `Self: Display`
+// for the former and none for the latter. Nothing is shown field by field, so
+// `#[debug]` attributes on fields or variants are rejected rather than ignored.
+fn derive_debug_forwarded(input: &DeriveInput, options: StructOptions) -> Result<TokenStream> {
+    let mode = options.mode.as_ref().unwrap().key();
+    if let Some(attr) = inner_debug_attr(&input.data) {
+        return Err(Error::new_spanned(
+            attr,
+            format!(
+                "`#[debug]` attributes inside a type with `{}` would be ignored",
+                mode
+            ),
+        ));
+    }
+
+    let name = &input.ident;
+    let f = formatter();
+    let (bound, body) = match &options.mode {
+        Some(Mode::With(function)) => (vec![], quote!(#function(self, #f))),
+        _ => (
+            vec![parse_quote!(Self: ::core::fmt::Display)],
+            quote!(::core::fmt::Display::fmt(self, #f)),
+        ),
+    };
+    let predicates = options.bound.unwrap_or(bound);
+    let generics = add_trait_bounds(input.generics.clone(), &[], predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, #f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+// The first `#[debug]` attribute on a variant or field of `data`.
+fn inner_debug_attr(data: &Data) -> Option<&Attribute> {
+    let is_debug = |attr: &&Attribute| attr.path.is_ident("debug");
+    match data {
+        Data::Struct(data) => data.fields.iter().flat_map(|f| &f.attrs).find(is_debug),
+        Data::Enum(data) => data.variants.iter().find_map(|variant| {
+            let fields = variant.fields.iter().flat_map(|f| &f.attrs);
+            variant.attrs.iter().chain(fields).find(is_debug)
         }),
-        _ => unimplemented!(),
+        Data::Union(data) => data
+            .fields
+            .named
+            .iter()
+            .flat_map(|f| &f.attrs)
+            .find(is_debug),
+    }
+}
+
+// Implement `Debug` for a union. Which field is active isn't known, so none
+// of them can be read and the union is shown as `Name { .. }`. A union whose
+// contents can be shown should use `#[debug(with = "...")]` instead.
+fn derive_debug_union(input: &DeriveInput, options: StructOptions) -> Result<TokenStream> {
+    if let Some(attr) = inner_debug_attr(&input.data) {
+        return Err(Error::new_spanned(
+            attr,
+            r#"union fields can't be shown, use `#[debug(with = "...")]` on the union instead"#,
+        ));
+    }
+
+    let name = &input.ident;
+    let name_str = match &options.name {
+        Some(name) => name.value(),
+        None => name.unraw().to_string(),
+    };
+    let generics = add_trait_bounds(
+        input.generics.clone(),
+        &[],
+        options.bound.unwrap_or_default(),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let f = formatter();
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, #f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #f.debug_struct(#name_str).finish_non_exhaustive()
+            }
+        }
+    })
+}
+
+// Whether `attrs` contain `#[repr(packed)]` or `#[repr(packed(N))]`.
+fn is_packed(attrs: &[Attribute]) -> Result<bool> {
+    for attr in attrs {
+        if !attr.path.is_ident("repr") {
+            continue;
+        }
+        if let Meta::List(list) = attr.parse_meta()? {
+            for nested in &list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("packed") => {
+                        return Ok(true)
+                    }
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("packed") => {
+                        return Ok(true)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok(false)
+}
+
+// A struct, or one variant of an enum.
+struct Variant<'a> {
+    // Path used to match on it, `Name` or `Name::Variant`.
+    path: TokenStream,
+    // Name shown in the output.
+    name: String,
+    fields: &'a Fields,
+    // Rule from `#[debug(rename_all = "...")]` for the field names.
+    rename_all: Option<RenameRule>,
+    // Whether to show just the single field, for `#[debug(transparent)]`.
+    transparent: bool,
+    // Whether to leave out `Option` fields which are `None`, for
+    // `#[debug(skip_none)]`.
+    skip_none: bool,
+    // Whether this is a `#[repr(packed)]` struct.
+    packed: bool,
+}
+
+impl<'a> Variant<'a> {
+    // Generated code looks like this:
+    // ```rust
+    // Name::Variant { a: field0, b: field1, c: _, d: field3 } => {
+    //     let mut debug = f.debug_struct("Variant");
+    //     debug.field("a", field0);
+    //     debug.field("b", &::core::format_args!("{:x}", *field1));
+    //     if !Vec::is_empty(field3) {
+    //         debug.field("d", field3);
+    //     }
+    //     debug.finish_non_exhaustive()
+    // }
+    // ```
+    // Tuple variants use `debug_tuple` and unit variants just write their name.
+    // A transparent struct formats its field directly instead, and packed
+    // structs are matched as described at `packed_pattern`.
+    // Skipped fields are not bound, and `finish_non_exhaustive()` marks that
+    // something was left out. Fields left out by `skip_if` or `skip_none` are
+    // not marked, since hiding them is the point.
+    fn debug_arm(&self, field_options: &[FieldOptions]) -> TokenStream {
+        let path = &self.path;
+        let name_str = &self.name;
+        let f = formatter();
+        let shown: Vec<usize> = field_options
+            .iter()
+            .enumerate()
+            .filter(|(_, options)| !matches!(options.render, Some(Render::Skip)))
+            .map(|(i, _)| i)
+            .collect();
+        let (pattern, prelude) = if self.packed {
+            packed_pattern(&self.path, self.fields, &shown)
+        } else {
+            let patterns =
+                field_options
+                    .iter()
+                    .enumerate()
+                    .map(|(i, options)| match options.render {
+                        Some(Render::Skip) => quote!(_),
+                        _ => binding(i).into_token_stream(),
+                    });
+            let pattern = match self.fields {
+                Fields::Named(fields) => {
+                    let names = fields.named.iter().map(|f| &f.ident);
+                    quote!(#path { #(#names: #patterns),* })
+                }
+                Fields::Unnamed(_) => quote!(#path(#(#patterns),*)),
+                Fields::Unit => quote!(#path),
+            };
+            (pattern, TokenStream::new())
+        };
+
+        if self.transparent {
+            let value = field_value_token(&binding(0), &field_options[0]);
+            return quote! {
+                #pattern => {
+                    #prelude
+                    ::core::fmt::Debug::fmt(#value, #f)
+                }
+            };
+        }
+
+        let finish = if shown.len() < field_options.len() {
+            quote!(finish_non_exhaustive)
+        } else {
+            quote!(finish)
+        };
+
+        let debug = Ident::new("debug", Span::mixed_site());
+        let calls = shown.iter().map(|&i| {
+            let field = binding(i);
+            let options = &field_options[i];
+            let value = field_value_token(&field, options);
+            let call = match self.fields {
+                Fields::Named(fields) => {
+                    let label = match &options.rename {
+                        Some(rename) => rename.value(),
+                        None => {
+                            let name = fields.named[i].ident.as_ref().unwrap();
+                            let name = name.unraw().to_string();
+                            match self.rename_all {
+                                Some(rule) => rule.apply(&name),
+                                None => name,
+                            }
+                        }
+                    };
+                    quote!(#debug.field(#label, #value);)
+                }
+                _ => quote!(#debug.field(#value);),
+            };
+            let ty = &self.fields.iter().nth(i).unwrap().ty;
+            match &options.skip_if {
+                Some(skip_if) => quote! {
+                    if !#skip_if(#field) {
+                        #call
+                    }
+                },
+                None if self.skip_none && is_option(ty) => quote! {
+                    if !::core::option::Option::is_none(#field) {
+                        #call
+                    }
+                },
+                None => call,
+            }
+        });
+
+        let begin = match self.fields {
+            Fields::Named(_) => quote!(debug_struct),
+            Fields::Unnamed(_) => quote!(debug_tuple),
+            Fields::Unit => {
+                return quote! {
+                    #pattern => #f.write_str(#name_str),
+                }
+            }
+        };
+        quote! {
+            #pattern => {
+                #prelude
+                let mut #debug = #f.#begin(#name_str);
+                #(#calls)*
+                #debug.#finish()
+            }
+        }
+    }
+}
+
+// Fields of a packed struct can't be borrowed in place, so the struct is
+// matched without binding anything and the `shown` fields are copied out
+// first:
+// ```rust
+// Name { .. } => {
+//     fn packed_fields_must_be_copy<T: Copy>() {}
+//     packed_fields_must_be_copy::<u16>();
+//     let field0 = &{ self.a };
+//     ...
+// }
+// ```
+// The call is spanned at the field's type, so a field which is not `Copy`
+// is reported there. Returns the pattern and the statements to put first in
+// the arm.
+fn packed_pattern(
+    path: &TokenStream,
+    fields: &Fields,
+    shown: &[usize],
+) -> (TokenStream, TokenStream) {
+    let copies = shown.iter().map(|&i| {
+        let field = binding(i);
+        let ty = &fields.iter().nth(i).unwrap().ty;
+        let member = member(fields, i);
+        let assert_copy = quote_spanned! {ty.span()=>
+            packed_fields_must_be_copy::<#ty>();
+        };
+        quote! {
+            #assert_copy
+            let #field = &{ self.#member };
+        }
+    });
+    let prelude = quote! {
+        fn packed_fields_must_be_copy<T: ::core::marker::Copy>() {}
+        #(#copies)*
+    };
+    (quote!(#path { .. }), prelude)
+}
+
+// The element type of `ty` if it is syntactically an array, slice or `Vec`,
+// possibly behind references.
+fn sequence_element(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(reference) => sequence_element(&reference.elem),
+        Type::Paren(paren) => sequence_element(&paren.elem),
+        Type::Group(group) => sequence_element(&group.elem),
+        Type::Array(array) => Some(&array.elem),
+        Type::Slice(slice) => Some(&slice.elem),
+        Type::Path(path) if path.qself.is_none() => generic_argument(ty, "Vec"),
+        _ => None,
+    }
+}
+
+// The `T` in `Wrapper<T>` if `ty` is syntactically a `Wrapper<T>`.
+fn generic_argument<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let last = path.segments.last().unwrap();
+    if last.ident != wrapper {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match &arguments.args[0] {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// Whether `ty` is syntactically a `String` or `str`, possibly behind
+// references.
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => is_string(&reference.elem),
+        Type::Paren(paren) => is_string(&paren.elem),
+        Type::Group(group) => is_string(&group.elem),
+        Type::Path(path) if path.qself.is_none() => {
+            let last = path.path.segments.last().unwrap();
+            (last.ident == "String" || last.ident == "str") && last.arguments.is_empty()
+        }
+        _ => false,
+    }
+}
+
+// Whether `ty` is syntactically an array, slice or `Vec` of `u8`.
+fn is_byte_buffer(ty: &Type) -> bool {
+    matches!(sequence_element(ty), Some(Type::Path(path)) if path.path.is_ident("u8"))
+}
+
+// Whether `ty` is syntactically an `Option<T>`.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let last = path.path.segments.last().unwrap();
+            last.ident == "Option" && matches!(last.arguments, PathArguments::AngleBracketed(_))
+        }
+        _ => false,
+    }
+}
+
+// The `Formatter` argument of the generated `fmt`, invisible to paths given
+// in attributes.
+fn formatter() -> Ident {
+    Ident::new("f", Span::mixed_site())
+}
+
+// The variable the `i`th field is bound to when matching on `self`.
+fn binding(i: usize) -> Ident {
+    Ident::new(&format!("field{}", i), Span::mixed_site())
+}
+
+// The value passed to `.field(...)` for a field bound by reference to
+// `binding`.
+fn field_value_token(binding: &Ident, options: &FieldOptions) -> TokenStream {
+    match &options.render {
+        None => quote!(#binding),
+        Some(Render::Format(format, _)) => quote!(&::core::format_args!(#format, *#binding)),
+        Some(Render::Redact(placeholder)) => quote!(&::core::format_args!("{}", #placeholder)),
+        Some(Render::RedactLen) => quote!(&::core::format_args!("<{} bytes>", #binding.len())),
+        Some(Render::With(function)) => quote!(&DebugWith(#binding, #function)),
+        Some(Render::MaxItems(max)) | Some(Render::MaxLen { max, text: false }) => {
+            quote!(&DebugItems(#binding.iter(), #max))
+        }
+        Some(Render::MaxLen { max, text: true }) => quote! {
+            &DebugStr(::core::convert::AsRef::<str>::as_ref(#binding), #max)
+        },
+        // Byte sequences in hex are shown as a single string of digits.
+        Some(render) if render.is_hex_bytes() => quote! {
+            &DebugWith(
+                &#binding[..],
+                |bytes: &[u8], f: &mut ::core::fmt::Formatter<'_>| {
+                    ::core::iter::Iterator::try_for_each(&mut bytes.iter(), |byte| {
+                        f.write_fmt(::core::format_args!("{:02x}", byte))
+                    })
+                },
+            )
+        },
+        Some(Render::Radix { radix, element }) => {
+            let spec = radix.format_spec();
+            match element {
+                Some(_) => quote! {
+                    &DebugEach(&#binding[..], |item, f| {
+                        f.write_fmt(::core::format_args!(#spec, *item))
+                    })
+                },
+                None => quote!(&::core::format_args!(#spec, *#binding)),
+            }
+        }
+        Some(Render::Skip) => unreachable!("skipped fields are not shown"),
+    }
+}
+
+#[derive(Default)]
+struct StructOptions {
+    // Where predicates given by `#[debug(bound = "...")]`, replacing the
+    // inferred bounds.
+    pub bound: Option<Vec<WherePredicate>>,
+    // Name shown instead of the struct's, given by `#[debug(name = "...")]`.
+    pub name: Option<LitStr>,
+    // Rule from `#[debug(rename_all = "...")]`, applied to the field names of
+    // a struct or the variant names of an enum.
+    pub rename_all: Option<RenameRule>,
+    // How the whole value is shown, when not field by field.
+    pub mode: Option<Mode>,
+    // `#[debug(skip_none)]`: leave out `Option` fields which are `None`.
+    pub skip_none: bool,
+}
+
+impl StructOptions {
+    // Set `mode`, failing if the type already has another way of being shown.
+    fn set_mode(&mut self, mode: Mode, tokens: impl ToTokens) -> Result<()> {
+        if let Some(previous) = &self.mode {
+            let message = if previous.key() == mode.key() {
+                format!("duplicate `{}`", mode.key())
+            } else {
+                format!(
+                    "`{}` cannot be used together with `{}`",
+                    mode.key(),
+                    previous.key()
+                )
+            };
+            return Err(Error::new_spanned(tokens, message));
+        }
+        self.mode = Some(mode);
+        Ok(())
+    }
+}
+
+enum Mode {
+    // `#[debug(transparent)]`: show only the single field of the struct.
+    Transparent(Path),
+    // `#[debug(display)]`: show the value with its `Display` impl.
+    Display,
+    // `#[debug(with = "path::to::fn")]`: show the value by calling this
+    // function.
+    With(Path),
+}
+
+impl Mode {
+    fn key(&self) -> &'static str {
+        match self {
+            Mode::Transparent(_) => "transparent",
+            Mode::Display => "display",
+            Mode::With(_) => "with",
+        }
+    }
+}
+
+// Parse struct level `#[debug(...)]` attributes.
+fn parse_struct_options(attrs: &[Attribute]) -> Result<StructOptions> {
+    let mut options = StructOptions::default();
+    for attr in attrs {
+        if !attr.path.is_ident("debug") {
+            continue;
+        }
+        for nested in parse_meta_list(attr, STRUCT_OPTIONS_EXPECTED)? {
+            match &nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(bound),
+                    ..
+                })) if path.is_ident("bound") => {
+                    if options.bound.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `bound`"));
+                    }
+                    options.bound = Some(parse_bound(bound)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(name),
+                    ..
+                })) if path.is_ident("name") => {
+                    if options.name.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `name`"));
+                    }
+                    options.name = Some(name.clone());
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(rule),
+                    ..
+                })) if path.is_ident("rename_all") => {
+                    if options.rename_all.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `rename_all`"));
+                    }
+                    options.rename_all = Some(RenameRule::parse(rule)?);
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("transparent") => {
+                    options.set_mode(Mode::Transparent(path.clone()), &nested)?;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("display") => {
+                    options.set_mode(Mode::Display, &nested)?;
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(function),
+                    ..
+                })) if path.is_ident("with") => {
+                    options.set_mode(Mode::With(function.parse()?), &nested)?;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip_none") => {
+                    if options.skip_none {
+                        return Err(Error::new_spanned(nested, "duplicate `skip_none`"));
+                    }
+                    options.skip_none = true;
+                }
+                _ => {
+                    return Err(unknown_option(
+                        &nested,
+                        STRUCT_OPTIONS,
+                        STRUCT_OPTIONS_EXPECTED,
+                    ))
+                }
+            }
+        }
+    }
+    Ok(options)
+}
+
+const STRUCT_OPTIONS: &[&str] = &[
+    "bound",
+    "name",
+    "rename_all",
+    "transparent",
+    "display",
+    "with",
+    "skip_none",
+];
+const STRUCT_OPTIONS_EXPECTED: &str = r#"expected `debug(...)` with one of `bound = "..."`, `name = "..."`, `rename_all = "..."`, `transparent`, `display`, `with = "..."`, `skip_none`"#;
+
+#[derive(Default)]
+struct VariantOptions {
+    // Name shown instead of the variant's, given by `#[debug(name = "...")]`.
+    pub name: Option<LitStr>,
+    // Rule from `#[debug(rename_all = "...")]` for the variant's field names.
+    pub rename_all: Option<RenameRule>,
+}
+
+// Parse variant level `#[debug(...)]` attributes.
+fn parse_variant_options(attrs: &[Attribute]) -> Result<VariantOptions> {
+    let mut options = VariantOptions::default();
+    for attr in attrs {
+        if !attr.path.is_ident("debug") {
+            continue;
+        }
+        for nested in parse_meta_list(attr, VARIANT_OPTIONS_EXPECTED)? {
+            match &nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(name),
+                    ..
+                })) if path.is_ident("name") => {
+                    if options.name.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `name`"));
+                    }
+                    options.name = Some(name.clone());
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(rule),
+                    ..
+                })) if path.is_ident("rename_all") => {
+                    if options.rename_all.is_some() {
+                        return Err(Error::new_spanned(nested, "duplicate `rename_all`"));
+                    }
+                    options.rename_all = Some(RenameRule::parse(rule)?);
+                }
+                _ => {
+                    return Err(unknown_option(
+                        &nested,
+                        VARIANT_OPTIONS,
+                        VARIANT_OPTIONS_EXPECTED,
+                    ))
+                }
+            }
+        }
+    }
+    Ok(options)
+}
+
+const VARIANT_OPTIONS: &[&str] = &["name", "rename_all"];
+const VARIANT_OPTIONS_EXPECTED: &str =
+    r#"expected `debug(...)` with one of `name = "..."`, `rename_all = "..."`"#;
+
+// Case conversion for `#[debug(rename_all = "...")]`.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(rule: &LitStr) -> Result<Self> {
+        Ok(match rule.value().as_str() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => {
+                return Err(Error::new(
+                    rule.span(),
+                    "unknown rename rule, expected one of \"lowercase\", \"UPPERCASE\", \"PascalCase\", \"camelCase\", \"snake_case\", \"SCREAMING_SNAKE_CASE\", \"kebab-case\", \"SCREAMING-KEBAB-CASE\"",
+                ))
+            }
+        })
+    }
+
+    // Apply the rule to a snake_case field name or PascalCase variant name.
+    // Like serde, "lowercase" and "UPPERCASE" only change the case, so
+    // `user_id` becomes `USER_ID` and `KeepAlive` becomes `keepalive`.
+    fn apply(self, name: &str) -> String {
+        // Split into lowercase words at underscores and before uppercase
+        // letters which follow a lowercase letter or digit.
+        let mut words: Vec<String> = vec![];
+        let mut previous: Option<char> = None;
+        for c in name.chars() {
+            if c == '_' {
+                previous = None;
+                continue;
+            }
+            let boundary = match previous {
+                None => true,
+                Some(p) => c.is_uppercase() && (p.is_lowercase() || p.is_ascii_digit()),
+            };
+            if boundary {
+                words.push(String::new());
+            }
+            words.last_mut().unwrap().extend(c.to_lowercase());
+            previous = Some(c);
+        }
+
+        let capitalize = |word: &String| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        };
+        match self {
+            RenameRule::Lower => name.to_lowercase(),
+            RenameRule::Upper => name.to_uppercase(),
+            RenameRule::Pascal => words.iter().map(capitalize).collect(),
+            RenameRule::Camel => {
+                let mut camel = words.first().cloned().unwrap_or_default();
+                camel.extend(words.iter().skip(1).map(capitalize));
+                camel
+            }
+            RenameRule::Snake => words.join("_"),
+            RenameRule::ScreamingSnake => words.join("_").to_uppercase(),
+            RenameRule::Kebab => words.join("-"),
+            RenameRule::ScreamingKebab => words.join("-").to_uppercase(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    // How the value is shown, when not with its own `Debug` impl.
+    pub render: Option<Render>,
+    // Where predicates given by `#[debug(bound = "...")]`, replacing the
+    // bounds inferred from this field.
+    pub bound: Option<Vec<WherePredicate>>,
+    // Name shown instead of the field's, given by `#[debug(rename = "...")]`.
+    pub rename: Option<LitStr>,
+    // Predicate from `#[debug(skip_if = "...")]`; the field is left out when
+    // it returns true.
+    pub skip_if: Option<Path>,
+}
+
+impl FieldOptions {
+    // Set `render`, failing if the field already has another way of being
+    // shown.
+    fn set_render(&mut self, render: Render, tokens: impl ToTokens) -> Result<()> {
+        if let Some(previous) = &self.render {
+            let message = if previous.key() == render.key() {
+                format!("duplicate `{}`", render.key())
+            } else {
+                format!(
+                    "`{}` cannot be used together with `{}`",
+                    render.key(),
+                    previous.key()
+                )
+            };
+            return Err(Error::new_spanned(tokens, message));
+        }
+        self.render = Some(render);
+        Ok(())
+    }
+
+    // Whether the field's own `Debug` impl is used, so its type needs a bound.
+    fn uses_debug(&self) -> bool {
+        matches!(
+            self.render,
+            None | Some(Render::MaxItems(_)) | Some(Render::MaxLen { .. })
+        )
+    }
+}
+
+enum Render {
+    // `#[debug = "..."]`: format the value with this format string, along
+    // with the formatting traits its placeholders use.
+    Format(LitStr, Vec<Ident>),
+    // `#[debug(skip)]`: leave the field out.
+    Skip,
+    // `#[debug(redact)]` or `#[debug(redact = "...")]`: show this placeholder
+    // instead of the value.
+    Redact(LitStr),
+    // `#[debug(redact_len)]`: show only the length of the value.
+    RedactLen,
+    // `#[debug(with = "path::to::fn")]`: show the value by calling this
+    // function.
+    With(Path),
+    // `#[debug(max_items = N)]`: show at most this many items of a sequence.
+    MaxItems(usize),
+    // `#[debug(max_len = N)]`: show at most this many characters of a string,
+    // or bytes of anything else.
+    MaxLen {
+        max: usize,
+        text: bool,
+    },
+    // `#[debug(hex)]`, `#[debug(binary)]`, `#[debug(octal)]` or
+    // `#[debug(ptr)]`: show the value, or each element of an array, slice or
+    // `Vec`, with that formatting trait. The element type is boxed to keep
+    // `Render` small.
+    Radix {
+        radix: Radix,
+        element: Option<Box<Type>>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Radix {
+    LowerHex,
+    Binary,
+    Octal,
+    Pointer,
+}
+
+impl Radix {
+    fn from_key(key: &Ident) -> Option<Self> {
+        Some(match key.to_string().as_str() {
+            "hex" => Radix::LowerHex,
+            "binary" => Radix::Binary,
+            "octal" => Radix::Octal,
+            "ptr" => Radix::Pointer,
+            _ => return None,
+        })
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Radix::LowerHex => "hex",
+            Radix::Binary => "binary",
+            Radix::Octal => "octal",
+            Radix::Pointer => "ptr",
+        }
+    }
+
+    fn format_spec(self) -> &'static str {
+        match self {
+            Radix::LowerHex => "{:#x}",
+            Radix::Binary => "{:#b}",
+            Radix::Octal => "{:#o}",
+            Radix::Pointer => "{:p}",
+        }
+    }
+
+    fn format_trait(self) -> Ident {
+        let name = match self {
+            Radix::LowerHex => "LowerHex",
+            Radix::Binary => "Binary",
+            Radix::Octal => "Octal",
+            Radix::Pointer => "Pointer",
+        };
+        Ident::new(name, Span::call_site())
+    }
+}
+
+impl Render {
+    // Whether this is `#[debug(hex)]` on a sequence of `u8`.
+    fn is_hex_bytes(&self) -> bool {
+        match self {
+            Render::Radix {
+                radix: Radix::LowerHex,
+                element: Some(element),
+            } => matches!(&**element, Type::Path(path) if path.path.is_ident("u8")),
+            _ => false,
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Render::Format(..) => "debug = \"...\"",
+            Render::Skip => "skip",
+            Render::Redact(_) => "redact",
+            Render::RedactLen => "redact_len",
+            Render::With(_) => "with",
+            Render::MaxItems(_) => "max_items",
+            Render::MaxLen { .. } => "max_len",
+            Render::Radix { radix, .. } => radix.key(),
+        }
+    }
+}
+
+// Parse field level `#[debug ...]` attributes.
+fn parse_field_options(field: &Field) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in &field.attrs {
+        if !attr.path.is_ident("debug") {
+            continue;
+        }
+        match attr.parse_meta()? {
+            Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(format),
+                ..
+            }) => {
+                let traits = check_format_string(&format)?;
+                options.set_render(Render::Format(format, traits), attr)?;
+            }
+            Meta::List(list) => {
+                for nested in list.nested {
+                    match &nested {
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(bound),
+                            ..
+                        })) if path.is_ident("bound") => {
+                            if options.bound.is_some() {
+                                return Err(Error::new_spanned(nested, "duplicate `bound`"));
+                            }
+                            options.bound = Some(parse_bound(bound)?);
+                        }
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                            if options.skip_if.is_some() {
+                                return Err(Error::new_spanned(
+                                    nested,
+                                    "`skip` cannot be used together with `skip_if`",
+                                ));
+                            }
+                            options.set_render(Render::Skip, &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("redact") => {
+                            let placeholder = LitStr::new("<redacted>", path.span());
+                            options.set_render(Render::Redact(placeholder), &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(placeholder),
+                            ..
+                        })) if path.is_ident("redact") => {
+                            options.set_render(Render::Redact(placeholder.clone()), &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("redact_len") => {
+                            // `len()` only counts bytes for these.
+                            if !is_string(&field.ty) && !is_byte_buffer(&field.ty) {
+                                return Err(Error::new_spanned(
+                                    nested,
+                                    "`redact_len` can only be used on a `String`, `str` or byte buffer like `Vec<u8>`",
+                                ));
+                            }
+                            options.set_render(Render::RedactLen, &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(function),
+                            ..
+                        })) if path.is_ident("with") => {
+                            options.set_render(Render::With(function.parse()?), &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::Path(path))
+                            if path.get_ident().and_then(Radix::from_key).is_some() =>
+                        {
+                            let radix = path.get_ident().and_then(Radix::from_key).unwrap();
+                            let element = sequence_element(&field.ty).cloned().map(Box::new);
+                            options.set_render(Render::Radix { radix, element }, &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Int(max),
+                            ..
+                        })) if path.is_ident("max_items") => {
+                            let max = max.base10_parse()?;
+                            options.set_render(Render::MaxItems(max), &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Int(max),
+                            ..
+                        })) if path.is_ident("max_len") => {
+                            let max = max.base10_parse()?;
+                            let text = is_string(&field.ty);
+                            options.set_render(Render::MaxLen { max, text }, &nested)?;
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(rename),
+                            ..
+                        })) if path.is_ident("rename") => {
+                            if options.rename.is_some() {
+                                return Err(Error::new_spanned(nested, "duplicate `rename`"));
+                            }
+                            options.rename = Some(rename.clone());
+                        }
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(skip_if),
+                            ..
+                        })) if path.is_ident("skip_if") => {
+                            if options.skip_if.is_some() {
+                                return Err(Error::new_spanned(nested, "duplicate `skip_if`"));
+                            }
+                            if let Some(Render::Skip) = options.render {
+                                return Err(Error::new_spanned(
+                                    nested,
+                                    "`skip_if` cannot be used together with `skip`",
+                                ));
+                            }
+                            options.skip_if = Some(skip_if.parse()?);
+                        }
+                        _ => {
+                            return Err(unknown_option(
+                                &nested,
+                                FIELD_OPTIONS,
+                                FIELD_OPTIONS_EXPECTED,
+                            ))
+                        }
+                    }
+                }
+            }
+            meta => return Err(Error::new_spanned(meta, FIELD_OPTIONS_EXPECTED)),
+        }
+    }
+    Ok(options)
+}
+
+const FIELD_OPTIONS: &[&str] = &[
+    "bound",
+    "skip",
+    "redact",
+    "redact_len",
+    "with",
+    "rename",
+    "skip_if",
+    "max_items",
+    "max_len",
+    "hex",
+    "binary",
+    "octal",
+    "ptr",
+];
+const FIELD_OPTIONS_EXPECTED: &str = r#"expected `debug = "..."` or `debug(...)` with one of `bound = "..."`, `skip`, `redact`, `redact = "..."`, `redact_len`, `with = "..."`, `rename = "..."`, `skip_if = "..."`, `max_items = N`, `max_len = N`, `hex`, `binary`, `octal`, `ptr`"#;
+
+// Error for a nested item which matched none of the options. A misspelled key
+// gets a suggestion, like "unknown option `skp`, did you mean `skip`?", and is
+// reported at the key. A known key written in the wrong form, like
+// `skip = "yes"`, is reported with the list of accepted forms.
+fn unknown_option(nested: &NestedMeta, keys: &[&str], expected: &str) -> Error {
+    let key = match nested {
+        NestedMeta::Meta(meta) => meta.path(),
+        NestedMeta::Lit(_) => return Error::new_spanned(nested, expected),
+    };
+    let key_str = match key.get_ident() {
+        Some(ident) if !keys.contains(&ident.to_string().as_str()) => ident.to_string(),
+        _ => return Error::new_spanned(nested, expected),
+    };
+    let message = match closest_key(&key_str, keys) {
+        Some(suggestion) => format!(
+            "unknown option `{}`, did you mean `{}`?",
+            key_str, suggestion
+        ),
+        None => format!("unknown option `{}`, {}", key_str, expected),
+    };
+    Error::new_spanned(key, message)
+}
+
+// The key in `keys` closest to `found` by edit distance, if it is close enough
+// to be a likely typo.
+fn closest_key<'a>(found: &str, keys: &[&'a str]) -> Option<&'a str> {
+    keys.iter()
+        .map(|key| (edit_distance(found, key), *key))
+        .filter(|(distance, key)| *distance <= key.len() / 3 + 1)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, key)| key)
+}
+
+// Levenshtein distance between `a` and `b`, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// Parse `attr` as `#[debug(...)]`, returning the nested items.
+fn parse_meta_list(attr: &Attribute, expected: &str) -> Result<Vec<NestedMeta>> {
+    match attr.parse_meta()? {
+        Meta::List(list) => Ok(list.nested.into_iter().collect()),
+        meta => Err(Error::new_spanned(meta, expected)),
+    }
+}
+
+// Parse the comma separated where predicates inside a `bound = "..."` string.
+// Parse errors are reported at the string literal.
+fn parse_bound(bound: &LitStr) -> Result<Vec<WherePredicate>> {
+    let predicates = bound.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+    Ok(predicates.into_iter().collect())
+}
+
+// Check that `format` is a valid format string which formats exactly one
+// value, so that mistakes are reported at the attribute instead of inside the
+// generated `format_args!`. Returns the formatting traits its placeholders use.
+fn check_format_string(format: &LitStr) -> Result<Vec<Ident>> {
+    let value = format.value();
+    let mut chars = value.chars().peekable();
+    let mut implicit = 0;
+    let mut traits: Vec<Ident> = vec![];
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') | None => {
+                            return Err(Error::new(
+                                format.span(),
+                                "unterminated `{` in format string",
+                            ))
+                        }
+                        Some(c) => placeholder.push(c),
+                    }
+                }
+                let (arg, spec) = match placeholder.split_once(':') {
+                    Some((arg, spec)) => (arg, spec),
+                    None => (placeholder.as_str(), ""),
+                };
+                match arg.trim() {
+                    "" => implicit += 1,
+                    "0" => {}
+                    _ => {
+                        return Err(Error::new(
+                            format.span(),
+                            format!("format string must refer to the field as `{{}}` or `{{0}}`, found `{{{}}}`", placeholder),
+                        ))
+                    }
+                }
+                let unaligned = strip_fill_align(spec);
+                if unaligned.contains('$') || unaligned.contains('*') {
+                    return Err(Error::new(
+                        format.span(),
+                        format!(
+                            "format spec `{}` takes a width or precision argument, but only the field is formatted",
+                            spec
+                        ),
+                    ));
+                }
+                if !is_valid_format_spec(spec) {
+                    return Err(Error::new(
+                        format.span(),
+                        format!("invalid format spec `{}`", spec),
+                    ));
+                }
+                let format_trait = format_trait(spec);
+                if !traits.contains(&format_trait) {
+                    traits.push(format_trait);
+                }
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '}' => return Err(Error::new(format.span(), "unmatched `}` in format string")),
+            _ => {}
+        }
+    }
+
+    if traits.is_empty() {
+        return Err(Error::new(
+            format.span(),
+            "format string must contain a placeholder for the field",
+        ));
+    }
+    if implicit > 1 {
+        return Err(Error::new(
+            format.span(),
+            "format string has more than one `{}` but formats a single field",
+        ));
+    }
+    Ok(traits)
+}
+
+// `spec` without its leading `[[fill]align]`, so that a fill like `*` or `$`
+// isn't mistaken for part of the width.
+fn strip_fill_align(spec: &str) -> &str {
+    let is_align = |c: char| matches!(c, '<' | '^' | '>');
+    let mut chars = spec.chars();
+    match (chars.next(), chars.next()) {
+        (Some(fill), Some(align)) if is_align(align) => &spec[fill.len_utf8() + 1..],
+        (Some(align), _) if is_align(align) => &spec[1..],
+        _ => spec,
+    }
+}
+
+// Whether `spec` matches `[[fill]align][sign]['#']['0'][width]['.' precision][type]`
+// with only literal widths and precisions, since there is just one argument.
+fn is_valid_format_spec(spec: &str) -> bool {
+    let mut rest = strip_fill_align(spec);
+    rest = rest.strip_prefix(|c| c == '+' || c == '-').unwrap_or(rest);
+    rest = rest.strip_prefix('#').unwrap_or(rest);
+    rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+    if let Some(precision) = rest.strip_prefix('.') {
+        rest = precision.trim_start_matches(|c: char| c.is_ascii_digit());
+        if rest.len() == precision.len() {
+            return false;
+        }
+    }
+    matches!(
+        rest,
+        "" | "?" | "x?" | "X?" | "x" | "X" | "o" | "b" | "e" | "E" | "p"
+    )
+}
+
+fn derive_custom_display(input: &DeriveInput) -> Result<TokenStream> {
+    let name = &input.ident;
+
+    // Every struct or variant, with its `#[display("...")]` template.
+    let variants: Vec<(TokenStream, &Fields, LitStr)> = match &input.data {
+        Data::Struct(data) => {
+            let template = parse_display_template(&input.attrs)?.ok_or_else(|| {
+                Error::new_spanned(name, r#"missing `#[display("...")]` attribute"#)
+            })?;
+            vec![(quote!(#name), &data.fields, template)]
+        }
+        Data::Enum(data) => {
+            if let Some(attr) = input
+                .attrs
+                .iter()
+                .find(|attr| attr.path.is_ident("display"))
+            {
+                return Err(Error::new_spanned(
+                    attr,
+                    r#"`#[display("...")]` goes on each variant of an enum"#,
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let variant_name = &variant.ident;
+                    let template = match parse_display_template(&variant.attrs)? {
+                        Some(template) => template,
+                        // Unit variants are shown as their name by default.
+                        None if variant.fields.is_empty() => {
+                            LitStr::new(&variant_name.unraw().to_string(), variant_name.span())
+                        }
+                        None => {
+                            return Err(Error::new_spanned(
+                                variant_name,
+                                r#"missing `#[display("...")]` attribute"#,
+                            ))
+                        }
+                    };
+                    Ok((quote!(#name::#variant_name), &variant.fields, template))
+                })
+                .collect::<Result<_>>()?
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                name,
+                "CustomDisplay does not support unions",
+            ))
+        }
     };
 
-    let expanded = quote! {
-        impl std::fmt::Debug for #name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                f.debug_struct(#name_str)
-                    #(#recurse)*
-                    .finish()
+    let packed = matches!(input.data, Data::Struct(_)) && is_packed(&input.attrs)?;
+    let params = type_params(&input.generics);
+
+    let f = formatter();
+    let mut predicates = vec![];
+    let mut arms = vec![];
+    for (path, fields, template) in &variants {
+        let (format, used) = parse_display_placeholders(template, fields)?;
+
+        // Each field needs the formatting trait of every placeholder using it,
+        // inferred the same way as the `Debug` bounds.
+        for (i, format_trait) in &used {
+            let field = fields.iter().nth(*i).unwrap();
+            let mut bounded = vec![];
+            collect_bounded_types(&field.ty, &input.generics, &mut bounded);
+            for ty in bounded {
+                let predicate: WherePredicate = parse_quote!(#ty: ::core::fmt::#format_trait);
+                let key = quote!(#predicate).to_string();
+                if !predicates
+                    .iter()
+                    .any(|p: &WherePredicate| quote!(#p).to_string() == key)
+                {
+                    predicates.push(predicate);
+                }
+            }
+        }
+
+        let mut indices: Vec<usize> = used.iter().map(|(i, _)| *i).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let args = used.iter().map(|(i, _)| binding(*i));
+        if packed {
+            // Fields are copied out as for `CustomDebug`, see `packed_pattern`.
+            for &i in &indices {
+                let ty = &fields.iter().nth(i).unwrap().ty;
+                if mentions_param(ty.to_token_stream(), &params) {
+                    predicates.push(parse_quote!(#ty: ::core::marker::Copy));
+                }
+            }
+            let (pattern, prelude) = packed_pattern(path, fields, &indices);
+            arms.push(quote! {
+                #pattern => {
+                    #prelude
+                    ::core::write!(#f, #format #(, #args)*)
+                }
+            });
+        } else {
+            let members = indices.iter().map(|&i| member(fields, i));
+            let bindings: Vec<Ident> = indices.iter().map(|&i| binding(i)).collect();
+            arms.push(quote! {
+                #path { #(#members: #bindings,)* .. } => ::core::write!(#f, #format #(, #args)*),
+            });
+        }
+    }
+    let generics = add_trait_bounds(input.generics.clone(), &[], predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // An enum without variants can't be matched through a reference.
+    let body = if arms.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote! {
+            match self {
+                #(#arms)*
             }
         }
     };
 
-    proc_macro::TokenStream::from(expanded)
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, #f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+// The name of the `i`th field for use in a pattern, `name` or `0`.
+fn member(fields: &Fields, i: usize) -> TokenStream {
+    match &fields.iter().nth(i).unwrap().ident {
+        Some(name) => quote!(#name),
+        None => {
+            let index = syn::Index::from(i);
+            quote!(#index)
+        }
+    }
+}
+
+// Find the template in `#[display("...")]`, if there is one.
+fn parse_display_template(attrs: &[Attribute]) -> Result<Option<LitStr>> {
+    let mut template = None;
+    for attr in attrs {
+        if !attr.path.is_ident("display") {
+            continue;
+        }
+        let nested = parse_meta_list(attr, DISPLAY_EXPECTED)?;
+        match nested.as_slice() {
+            [NestedMeta::Lit(Lit::Str(lit))] => {
+                if template.is_some() {
+                    return Err(Error::new_spanned(attr, "duplicate `display` attribute"));
+                }
+                template = Some(lit.clone());
+            }
+            _ => return Err(Error::new_spanned(attr, DISPLAY_EXPECTED)),
+        }
+    }
+    Ok(template)
+}
+
+const DISPLAY_EXPECTED: &str = r#"expected `display("...")`"#;
+
+// Check the placeholders of a `#[display("...")]` template against `fields`.
+// Placeholders name a field, like `{name}` or `{0}`, optionally followed by a
+// format spec. Returns the template rewritten to use positional arguments,
+// along with the field index and formatting trait of each argument.
+fn parse_display_placeholders(
+    template: &LitStr,
+    fields: &Fields,
+) -> Result<(LitStr, Vec<(usize, Ident)>)> {
+    let value = template.value();
+    let mut chars = value.chars().peekable();
+    let mut format = String::new();
+    let mut used = vec![];
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                format.push_str("{{");
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') | None => {
+                            return Err(Error::new(
+                                template.span(),
+                                "unterminated `{` in format string",
+                            ))
+                        }
+                        Some(c) => placeholder.push(c),
+                    }
+                }
+                let (arg, spec) = match placeholder.split_once(':') {
+                    Some((arg, spec)) => (arg.trim(), spec),
+                    None => (placeholder.trim(), ""),
+                };
+                let index = fields
+                    .iter()
+                    .enumerate()
+                    .position(|(i, field)| match &field.ident {
+                        Some(name) => name.unraw() == arg,
+                        None => i.to_string() == arg,
+                    });
+                let index = match index {
+                    Some(index) => index,
+                    None if arg.is_empty() => {
+                        return Err(Error::new(
+                            template.span(),
+                            "placeholders must name a field, like `{name}` or `{0}`",
+                        ))
+                    }
+                    None => {
+                        return Err(Error::new(
+                            template.span(),
+                            format!("no field `{}` to format", arg),
+                        ))
+                    }
+                };
+                if !is_valid_format_spec(spec) {
+                    return Err(Error::new(
+                        template.span(),
+                        format!("invalid format spec `{}`", spec),
+                    ));
+                }
+                format.push_str(&format!("{{{}:{}}}", used.len(), spec));
+                used.push((index, format_trait(spec)));
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                format.push_str("}}");
+            }
+            '}' => {
+                return Err(Error::new(
+                    template.span(),
+                    "unmatched `}` in format string",
+                ))
+            }
+            c => format.push(c),
+        }
+    }
+    Ok((LitStr::new(&format, template.span()), used))
+}
+
+// The formatting trait used by a valid format spec, such as `Debug` for `?`.
+fn format_trait(spec: &str) -> Ident {
+    let name = if spec.ends_with('?') {
+        "Debug"
+    } else if spec.ends_with('x') {
+        "LowerHex"
+    } else if spec.ends_with('X') {
+        "UpperHex"
+    } else if spec.ends_with('o') {
+        "Octal"
+    } else if spec.ends_with('b') {
+        "Binary"
+    } else if spec.ends_with('e') {
+        "LowerExp"
+    } else if spec.ends_with('E') {
+        "UpperExp"
+    } else if spec.ends_with('p') {
+        "Pointer"
+    } else {
+        "Display"
+    };
+    Ident::new(name, Span::call_site())
+}
+
+// Push a `ty: Trait` predicate for each type in `ty` which needs a bound to
+// implement the formatting trait `format_trait`, inferred the same way as the
+// `Debug` bounds. Pointers implement `Pointer` whatever they point to, so for
+// `Pointer` only a type mentioning a type parameter is bounded, as a whole.
+fn push_format_bounds(
+    ty: &Type,
+    format_trait: &Ident,
+    generics: &Generics,
+    predicates: &mut Vec<WherePredicate>,
+) {
+    let mut bounded = vec![];
+    if format_trait == "Pointer" {
+        if mentions_param(ty.to_token_stream(), &type_params(generics)) {
+            bounded.push(ty.clone());
+        }
+    } else {
+        collect_bounded_types(ty, generics, &mut bounded);
+    }
+    for ty in bounded {
+        predicates.push(parse_quote!(#ty: ::core::fmt::#format_trait));
+    }
+}
+
+// Add a `Debug` bound for every type in `bounded`, followed by the
+// `predicates` given by the caller.
+fn add_trait_bounds(
+    mut generics: Generics,
+    bounded: &[Type],
+    predicates: Vec<WherePredicate>,
+) -> Generics {
+    let where_clause = generics.make_where_clause();
+    for ty in bounded {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::core::fmt::Debug));
+    }
+    where_clause.predicates.extend(predicates);
+    generics
+}
+
+// Collect the types mentioned by `ty` which need a `Debug` bound: type
+// parameters used directly, and associated types of type parameters such as
+// `T::Value` or `<T as Trait>::Value`. The traversal looks through references,
+// arrays, slices, tuples and generic arguments. Type parameters only used
+// inside `PhantomData`, function pointers or raw pointers need no bound, since
+// those implement `Debug` regardless.
+fn collect_bounded_types(ty: &Type, generics: &Generics, bounded: &mut Vec<Type>) {
+    let params = type_params(generics);
+    if !params.is_empty() {
+        collect_types(ty, &params, bounded);
+    }
+}
+
+fn type_params(generics: &Generics) -> HashSet<&Ident> {
+    generics.type_params().map(|param| &param.ident).collect()
+}
+
+// Push `ty` unless an identical type is already in `types`.
+fn push_unique(types: &mut Vec<Type>, ty: &Type) {
+    let key = quote!(#ty).to_string();
+    if !types.iter().any(|t| quote!(#t).to_string() == key) {
+        types.push(ty.clone());
+    }
+}
+
+fn collect_types(ty: &Type, params: &HashSet<&Ident>, bounded: &mut Vec<Type>) {
+    match ty {
+        Type::Path(path) => match &path.qself {
+            // `<T as Trait>::Value` can only be bounded as a whole. The
+            // parameter may appear in the trait too, as in `<X as Trait<T>>::Value`.
+            Some(_) => {
+                if mentions_param(ty.to_token_stream(), params) {
+                    push_unique(bounded, ty);
+                }
+            }
+            None => collect_path_types(ty, &path.path, params, bounded),
+        },
+        Type::Reference(reference) => collect_types(&reference.elem, params, bounded),
+        Type::Array(array) => collect_types(&array.elem, params, bounded),
+        Type::Slice(slice) => collect_types(&slice.elem, params, bounded),
+        Type::Paren(paren) => collect_types(&paren.elem, params, bounded),
+        Type::Group(group) => collect_types(&group.elem, params, bounded),
+        Type::Tuple(tuple) => {
+            for elem in &tuple.elems {
+                collect_types(elem, params, bounded);
+            }
+        }
+        // Trait objects implement `Debug` only through their own impls, so
+        // one mentioning a type parameter is bounded as a whole.
+        Type::TraitObject(_) if mentions_param(ty.to_token_stream(), params) => {
+            push_unique(bounded, ty)
+        }
+        // There is no telling what a macro expands to, so its type is always
+        // bounded as a whole.
+        Type::Macro(_) => push_unique(bounded, ty),
+        // Function pointers, raw pointers and `!` implement `Debug` for any
+        // argument types, and `impl Trait` can't be the type of a field.
+        _ => {}
+    }
+}
+
+fn collect_path_types(ty: &Type, path: &Path, params: &HashSet<&Ident>, bounded: &mut Vec<Type>) {
+    let first = &path.segments[0];
+    if path.leading_colon.is_none() && params.contains(&first.ident) {
+        // Either `T` itself or an associated type like `T::Value`.
+        push_unique(bounded, ty);
+        return;
+    }
+
+    let last = path.segments.last().unwrap();
+    if last.ident == "PhantomData" {
+        return;
+    }
+    for segment in &path.segments {
+        if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
+            for arg in &arguments.args {
+                if let GenericArgument::Type(ty) = arg {
+                    collect_types(ty, params, bounded);
+                }
+            }
+        }
+    }
+}
+
+// Whether `tokens` mention any of the type parameters in `params`.
+fn mentions_param(tokens: TokenStream, params: &HashSet<&Ident>) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => params.contains(&ident),
+        TokenTree::Group(group) => mentions_param(group.stream(), params),
+        _ => false,
+    })
 }
@@ -0,0 +1,50 @@
+// Bounds are inferred per type parameter from how the fields mention it:
+//
+//   - `T` used directly as a field type gets `T: Debug`;
+//   - `U` only used inside `PhantomData<U>` gets no bound;
+//   - `V` whose associated type is used gets `V::Value: Debug` but no bound
+//     on `V` itself.
+//
+// The generated impl looks like:
+//
+//     impl<T, U, V: Trait> Debug for Wrapper<T, U, V>
+//     where
+//         T: Debug,
+//         V::Value: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T, U, V: Trait> {
+    value: T,
+    marker: PhantomData<U>,
+    values: Option<Vec<V::Value>>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+
+    impl Trait for NotDebug {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<u8, NotDebug, NotDebug>>();
+
+    let wrapper = Wrapper::<_, u8, NotDebug> {
+        value: "v",
+        marker: PhantomData,
+        values: Some(vec![1, 2]),
+    };
+    let debug = format!("{:?}", wrapper);
+    let expected = r#"Wrapper { value: "v", marker: PhantomData<u8>, values: Some([1, 2]) }"#;
+    assert_eq!(debug, expected);
+}
@@ -0,0 +1,32 @@
+// Format strings in #[debug = "..."] are checked when the derive expands, so a
+// mistake points at the attribute rather than at code the caller never wrote.
+// Each field is formatted as a single value, so the string must refer to it
+// exactly once through `{}` or `{0}`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Unbalanced {
+    #[debug = "{:?"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct TwoValues {
+    #[debug = "{} and {}"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct NamedArgument {
+    #[debug = "{value}"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct NoPlaceholder {
+    #[debug = "hidden"]
+    value: u8,
+}
+
+fn main() {}
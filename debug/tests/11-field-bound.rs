@@ -0,0 +1,45 @@
+// A #[debug(bound = "...")] attribute on a field replaces only the bounds that
+// would have been inferred from that field's type. Bounds inferred from the
+// other fields are kept, so `U: Debug` is still required below while the
+// `Field<T>` field contributes `T::Value: Debug` instead of `T: Debug`.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T::Value: Debug")]
+pub struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, u8>>();
+
+    let wrapper = Wrapper::<Id, _> {
+        field: Field { values: vec![1, 2] },
+        normal: "x",
+    };
+    assert_eq!(
+        format!("{:?}", wrapper),
+        r#"Wrapper { field: Field { values: [1, 2] }, normal: "x" }"#
+    );
+}
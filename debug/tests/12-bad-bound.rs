@@ -0,0 +1,18 @@
+// A bound that does not parse as a where predicate is reported at the string
+// literal in the attribute.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T: Debug,, U: Debug")]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T> {
+    #[debug(bound = "not a bound")]
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,68 @@
+// CustomDebug also works on enums, tuple structs and unit structs, producing
+// the same output as the standard library's derive: struct variants are
+// printed with `debug_struct`, tuple variants and tuple structs with
+// `debug_tuple`, and unit variants and unit structs as just their name.
+//
+// Field attributes and bound inference apply to the fields of every variant.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub enum Shape<T> {
+    Point,
+    Circle(T),
+    Rect {
+        #[debug = "{}px"]
+        width: u32,
+        height: u32,
+    },
+    Polygon(#[debug = "{:?}!"] Vec<T>, bool),
+}
+
+#[derive(CustomDebug)]
+pub struct Meters(#[debug = "{}m"] u32, u8);
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+// The variants' fields are named after the formatter argument and the
+// bindings the macro uses, which must not confuse it.
+#[derive(CustomDebug)]
+pub enum Shadowing {
+    Fields { f: u8, field0: u8 },
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Never>();
+
+    let shapes: Vec<Shape<u8>> = vec![
+        Shape::Point,
+        Shape::Circle(5),
+        Shape::Rect {
+            width: 3,
+            height: 4,
+        },
+        Shape::Polygon(vec![1, 2], true),
+    ];
+    assert_eq!(
+        format!("{:?}", shapes),
+        "[Point, Circle(5), Rect { width: 3px, height: 4 }, Polygon([1, 2]!, true)]",
+    );
+    assert_eq!(
+        format!("{:#?}", Shape::Circle(1u8)),
+        "Circle(\n    1,\n)",
+    );
+
+    assert_eq!(format!("{:?}", Meters(7, 1)), "Meters(7m, 1)");
+    assert_eq!(format!("{:?}", Unit), "Unit");
+    assert_eq!(
+        format!("{:?}", Shadowing::Fields { f: 1, field0: 2 }),
+        "Fields { f: 1, field0: 2 }",
+    );
+}
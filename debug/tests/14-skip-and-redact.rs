@@ -0,0 +1,54 @@
+// Fields holding secrets can be kept out of the output:
+//
+//   - #[debug(skip)] leaves the field out entirely, and the output ends with
+//     `..` to show that something was omitted.
+//   - #[debug(redact)] shows `<redacted>` in place of the value, and
+//     #[debug(redact = "***")] shows the given placeholder instead.
+//   - #[debug(redact_len)] shows only the length of a string or byte buffer.
+//
+// None of these need the field's type to implement Debug.
+
+use derive_debug::CustomDebug;
+
+pub struct Secret;
+
+#[derive(CustomDebug)]
+pub struct Login {
+    user: String,
+    #[debug(skip)]
+    key: Secret,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "***")]
+    pin: u32,
+    #[debug(redact_len)]
+    token: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub enum Credentials<T> {
+    Token(#[debug(redact_len)] String, #[debug(skip)] T),
+    Anonymous,
+}
+
+fn main() {
+    let login = Login {
+        user: "ferris".to_owned(),
+        key: Secret,
+        password: "hunter2".to_owned(),
+        pin: 1234,
+        token: vec![0; 16],
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "ferris", password: <redacted>, pin: ***, token: <16 bytes>, .. }"#,
+    );
+
+    // `T` is only used by a skipped field, so it needs no Debug bound.
+    let credentials = Credentials::Token("abc".to_owned(), Secret);
+    assert_eq!(format!("{:?}", credentials), "Token(<3 bytes>, ..)");
+    assert_eq!(
+        format!("{:?}", Credentials::<Secret>::Anonymous),
+        "Anonymous",
+    );
+}
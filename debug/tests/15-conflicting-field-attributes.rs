@@ -0,0 +1,25 @@
+// A field can be shown in only one way, so combining a format string, `skip`,
+// `redact` and `redact_len` on the same field is an error.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct SkipAndRedact {
+    #[debug(skip, redact)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct FormatAndRedactLen {
+    #[debug = "{:?}"]
+    #[debug(redact_len)]
+    value: String,
+}
+
+#[derive(CustomDebug)]
+pub struct DuplicateRedact {
+    #[debug(redact, redact = "***")]
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,64 @@
+// #[debug(with = "path::to::fn")] formats a field by calling a function with
+// the signature `fn(&T, &mut fmt::Formatter) -> fmt::Result`, where `T` is the
+// field's type. The field's type does not need to implement Debug itself.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+
+mod fmt_helpers {
+    use std::collections::{BTreeMap, HashMap};
+    use std::fmt::{self, Debug};
+
+    pub fn hex(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+
+    pub fn sorted<K: Ord + Debug, V: Debug>(
+        map: &HashMap<K, V>,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        let sorted: BTreeMap<&K, &V> = map.iter().collect();
+        sorted.fmt(f)
+    }
+}
+
+pub struct Opaque;
+
+fn opaque<T>(_: &T, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    f.write_str("?")
+}
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(with = "fmt_helpers::hex")]
+    payload: Vec<u8>,
+    #[debug(with = "fmt_helpers::sorted")]
+    headers: HashMap<&'static str, u32>,
+}
+
+#[derive(CustomDebug)]
+pub enum Message<T> {
+    Raw(#[debug(with = "opaque")] T),
+    Empty,
+}
+
+fn main() {
+    let mut headers = HashMap::new();
+    headers.insert("length", 3);
+    headers.insert("checksum", 255);
+    headers.insert("id", 7);
+    let packet = Packet {
+        payload: vec![0xde, 0xad, 0x01],
+        headers,
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        r#"Packet { payload: dead01, headers: {"checksum": 255, "id": 7, "length": 3} }"#,
+    );
+
+    assert_eq!(format!("{:?}", Message::Raw(Opaque)), "Raw(?)");
+    assert_eq!(format!("{:?}", Message::<Opaque>::Empty), "Empty");
+}
@@ -0,0 +1,85 @@
+// The names in the output can differ from the Rust identifiers:
+//
+//   - #[debug(rename = "...")] on a field changes its label.
+//   - #[debug(name = "...")] on a struct or an enum variant changes the name
+//     printed before the fields.
+//   - #[debug(rename_all = "...")] on a struct or an enum variant converts all
+//     of its field names to the given case, and on an enum converts all of its
+//     variant names. An explicit `rename` or `name` takes precedence.
+//
+// The supported cases are "lowercase", "UPPERCASE", "PascalCase", "camelCase",
+// "snake_case", "SCREAMING_SNAKE_CASE", "kebab-case" and
+// "SCREAMING-KEBAB-CASE". As in serde, "lowercase" and "UPPERCASE" only change
+// the case of the name and keep its underscores.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(name = "LoginRequest", rename_all = "camelCase")]
+pub struct Login {
+    user_name: &'static str,
+    #[debug(rename = "pwd")]
+    password_hash: u64,
+    r#type: u8,
+    retry_count_2: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Frame {
+    KeepAlive,
+    #[debug(rename_all = "kebab-case")]
+    DataChunk { stream_id: u32, end_of_stream: bool },
+    #[debug(name = "rst")]
+    ResetStream(u32),
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "UPPERCASE")]
+pub struct Header {
+    user_id: u32,
+    content_type: (),
+}
+
+#[derive(CustomDebug)]
+#[debug(rename_all = "lowercase")]
+pub enum Method {
+    GetAll,
+    #[debug(rename_all = "UPPERCASE")]
+    Put { user_id: u32 },
+}
+
+fn main() {
+    let login = Login {
+        user_name: "ferris",
+        password_hash: 7,
+        r#type: 1,
+        retry_count_2: 0,
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"LoginRequest { userName: "ferris", pwd: 7, type: 1, retryCount2: 0 }"#,
+    );
+
+    let frames = [
+        Frame::KeepAlive,
+        Frame::DataChunk {
+            stream_id: 3,
+            end_of_stream: true,
+        },
+        Frame::ResetStream(3),
+    ];
+    assert_eq!(
+        format!("{:?}", frames),
+        "[KEEP_ALIVE, DATA_CHUNK { stream-id: 3, end-of-stream: true }, rst(3)]",
+    );
+
+    let header = Header {
+        user_id: 1,
+        content_type: (),
+    };
+    assert_eq!(format!("{:?}", header), "Header { USER_ID: 1, CONTENT_TYPE: () }");
+
+    let methods = [Method::GetAll, Method::Put { user_id: 2 }];
+    assert_eq!(format!("{:?}", methods), "[getall, put { USER_ID: 2 }]");
+}
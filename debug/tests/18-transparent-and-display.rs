@@ -0,0 +1,59 @@
+// Newtypes often want to print as their contents rather than as a wrapper.
+//
+//   - #[debug(transparent)] on a struct with exactly one field formats just
+//     that field, so `UserId(42)` prints as `42`. Field attributes on the
+//     inner field still apply.
+//   - #[debug(display)] formats the value with its own Display impl. Field
+//     types then need no Debug bound, only `Self: Display`.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Display};
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Mask(#[debug = "{:#06x}"] u16);
+
+#[derive(CustomDebug)]
+#[debug(display)]
+pub struct Meters<T>(T);
+
+impl<T: Display> Display for Meters<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}m", self.0)
+    }
+}
+
+#[derive(CustomDebug)]
+#[debug(display)]
+pub enum Level {
+    Low,
+    High,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Level::Low => "low",
+            Level::High => "high",
+        })
+    }
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(42)), "42");
+    assert_eq!(format!("{:?}", Wrapper { inner: "x" }), r#""x""#);
+    assert_eq!(format!("{:?}", Wrapper { inner: UserId(1) }), "1");
+    assert_eq!(format!("{:?}", Mask(0xff)), "0x00ff");
+    assert_eq!(format!("{:?}", Meters(2.5)), "2.5m");
+    assert_eq!(format!("{:?}", [Level::Low, Level::High]), "[low, high]");
+}
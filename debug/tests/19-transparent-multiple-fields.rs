@@ -0,0 +1,21 @@
+// #[debug(transparent)] only makes sense when there is a single field to show
+// in place of the whole struct.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Point(i32, i32);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub enum Either {
+    Left(i32),
+    Right(i32),
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent, display)]
+pub struct Id(u64);
+
+fn main() {}
@@ -0,0 +1,69 @@
+// Fields can be left out of the output depending on their value:
+//
+//   - #[debug(skip_none)] on a struct or enum leaves out every `Option` field
+//     that is `None`.
+//   - #[debug(skip_if = "path::to::fn")] on a field leaves it out whenever the
+//     function, called with a reference to the field, returns true.
+//
+// Unlike #[debug(skip)], fields hidden this way do not add `..` to the output.
+
+use derive_debug::CustomDebug;
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+#[derive(CustomDebug)]
+#[debug(skip_none)]
+pub struct Config {
+    name: &'static str,
+    timeout: Option<u32>,
+    proxy: Option<&'static str>,
+    #[debug(skip_if = "Vec::is_empty")]
+    tags: Vec<&'static str>,
+    #[debug(skip_if = "is_zero")]
+    retries: u32,
+}
+
+#[derive(CustomDebug)]
+#[debug(skip_none)]
+pub enum Event {
+    Click(Option<u8>, Option<u8>),
+    Key {
+        code: u32,
+        #[debug(skip_if = "is_zero")]
+        modifiers: u32,
+    },
+}
+
+fn main() {
+    let config = Config {
+        name: "default",
+        timeout: None,
+        proxy: Some("localhost"),
+        tags: vec![],
+        retries: 0,
+    };
+    assert_eq!(
+        format!("{:?}", config),
+        r#"Config { name: "default", proxy: Some("localhost") }"#,
+    );
+
+    let config = Config {
+        name: "custom",
+        timeout: Some(5),
+        proxy: None,
+        tags: vec!["a"],
+        retries: 3,
+    };
+    assert_eq!(
+        format!("{:?}", config),
+        r#"Config { name: "custom", timeout: Some(5), tags: ["a"], retries: 3 }"#,
+    );
+
+    assert_eq!(format!("{:?}", Event::Click(None, Some(1))), "Click(Some(1))");
+    assert_eq!(
+        format!("{:?}", Event::Key { code: 13, modifiers: 0 }),
+        "Key { code: 13 }",
+    );
+}
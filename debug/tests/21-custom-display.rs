@@ -0,0 +1,75 @@
+// The crate also provides a CustomDisplay derive, which implements Display
+// from a template given in a #[display("...")] attribute on the struct or on
+// each enum variant. Placeholders name a field, `{name}` for named fields or
+// `{0}` for tuple fields, and may carry a format spec like `{id:>4}` or
+// `{tags:?}`. Unit variants without an attribute are shown as their name.
+//
+// Bounds are inferred like for CustomDebug, using the formatting trait each
+// placeholder asks for: `{value}` needs `T: Display` and `{value:?}` needs
+// `T: Debug`.
+
+use derive_debug::CustomDisplay;
+use std::fmt::Display;
+
+#[derive(CustomDisplay)]
+#[display("{name} ({id:>4})")]
+pub struct User {
+    id: u32,
+    name: &'static str,
+    #[allow(dead_code)]
+    password: &'static str,
+}
+
+#[derive(CustomDisplay)]
+#[display("{0:#x}..{1:#x}")]
+pub struct Range(u16, u16);
+
+#[derive(CustomDisplay)]
+pub enum Shape<T> {
+    #[display("circle of radius {0}")]
+    Circle(T),
+    #[display("{width}x{height} rectangle, {{tagged {tags:?}}}")]
+    Rect {
+        width: T,
+        height: T,
+        tags: Vec<&'static str>,
+    },
+    Point,
+}
+
+// Only needs `T: Debug`, not `T: Display`.
+#[derive(CustomDisplay)]
+#[display("list {items:?}")]
+pub struct List<T> {
+    items: Vec<T>,
+}
+
+#[derive(Debug)]
+pub struct NotDisplay;
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    let user = User {
+        id: 7,
+        name: "ferris",
+        password: "hunter2",
+    };
+    assert_eq!(user.to_string(), "ferris (   7)");
+    assert_eq!(Range(0, 255).to_string(), "0x0..0xff");
+
+    assert_eq!(Shape::Circle(1.5).to_string(), "circle of radius 1.5");
+    let rect = Shape::Rect {
+        width: 2,
+        height: 3,
+        tags: vec!["a"],
+    };
+    assert_eq!(rect.to_string(), r#"2x3 rectangle, {tagged ["a"]}"#);
+    assert_eq!(Shape::<u8>::Point.to_string(), "Point");
+
+    assert_display::<List<NotDisplay>>();
+    let list = List {
+        items: vec![NotDisplay],
+    };
+    assert_eq!(list.to_string(), "list [NotDisplay]");
+}
@@ -0,0 +1,23 @@
+// Placeholders in a #[display("...")] template are checked against the fields
+// when the derive expands, and mistakes are reported at the template.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+#[display("{name} ({identifier})")]
+pub struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(CustomDisplay)]
+#[display("{} and {1}")]
+pub struct Pair(u8, u8);
+
+#[derive(CustomDisplay)]
+pub enum Message {
+    Quit,
+    Write(String),
+}
+
+fn main() {}
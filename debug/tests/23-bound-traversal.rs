@@ -0,0 +1,104 @@
+// Bound inference looks through every kind of type a field can have, and
+// emits the smallest set of where-predicates it can:
+//
+//   - references, arrays, slices, tuples and generic arguments are looked
+//     through, so `&'a [T; 2]` gives `T: Debug`;
+//   - qualified paths like `<T as Trait>::Value` or `<X as Trait<T>>::Value`
+//     are bounded as a whole;
+//   - trait objects mentioning a type parameter are bounded as a whole;
+//   - function pointers and raw pointers need no bound at all;
+//   - types written with a macro are bounded as a whole, since there is no
+//     telling what they expand to.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+
+pub trait Trait {
+    type Value;
+}
+
+pub trait Shape<T> {
+    fn size(&self) -> T;
+}
+
+impl<T: Debug> Debug for dyn Shape<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Shape({:?})", self.size())
+    }
+}
+
+macro_rules! pair {
+    ($t:ty) => {
+        ($t, $t)
+    };
+}
+
+#[derive(CustomDebug)]
+pub struct Everything<'a, A, B, C, D, E: Trait, F, G, H> {
+    reference: &'a [A; 2],
+    slice: &'a [B],
+    tuple: (C, Option<D>),
+    qualified: <E as Trait>::Value,
+    callback: fn(F) -> F,
+    pointer: *const G,
+    shape: Box<dyn Shape<H>>,
+    pair: pair!(u8),
+}
+
+#[derive(CustomDebug)]
+pub struct Picked<T> {
+    value: <Chooser as Pick<T>>::Out,
+}
+
+pub trait Pick<T> {
+    type Out;
+}
+
+pub struct Chooser;
+
+impl<T> Pick<T> for Chooser {
+    type Out = T;
+}
+
+pub struct NotDebug;
+
+struct Id;
+
+impl Trait for Id {
+    type Value = u8;
+}
+
+struct Square;
+
+impl Shape<u32> for Square {
+    fn size(&self) -> u32 {
+        4
+    }
+}
+
+fn assert_debug<T: Debug>() {}
+
+fn main() {
+    // `Id` itself is not Debug, only its `Value`, and neither the function
+    // pointer nor the raw pointer arguments need to be.
+    assert_debug::<Everything<u8, u8, u8, u8, Id, NotDebug, NotDebug, u32>>();
+
+    let everything = Everything::<_, _, _, _, Id, _, _, _> {
+        reference: &[1, 2],
+        slice: &[true],
+        tuple: ('c', Some("d")),
+        qualified: 5,
+        callback: |x: NotDebug| x,
+        pointer: std::ptr::null::<NotDebug>(),
+        shape: Box::new(Square),
+        pair: (1, 2),
+    };
+    let debug = format!("{:?}", everything);
+    assert!(debug.starts_with(
+        r#"Everything { reference: [1, 2], slice: [true], tuple: ('c', Some("d")), qualified: 5, callback: "#
+    ));
+    assert!(debug.ends_with(", shape: Shape(4), pair: (1, 2) }"));
+
+    let picked = Picked::<u8> { value: 7 };
+    assert_eq!(format!("{:?}", picked), "Picked { value: 7 }");
+}
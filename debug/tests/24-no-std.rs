@@ -0,0 +1,77 @@
+// The generated code only refers to `::core`, so the derives work in
+// `#![no_std]` crates. Neither a module without the implicit prelude, nor
+// local redefinitions of `format_args!` and `write!`, nor a function called
+// `f` passed to `with` can break it.
+
+#![no_std]
+#![allow(dead_code, unused_macros)]
+
+extern crate std as real_std;
+
+use real_std::format;
+
+macro_rules! format_args {
+    ($($tt:tt)*) => {
+        compile_error!("format_args! must not be used")
+    };
+}
+
+macro_rules! write {
+    ($($tt:tt)*) => {
+        compile_error!("write! must not be used")
+    };
+}
+
+mod no_prelude {
+    #![no_implicit_prelude]
+
+    use ::derive_debug::{CustomDebug, CustomDisplay};
+
+    pub fn f(value: &u8, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        f.write_str(if *value == 0 { "zero" } else { "nonzero" })
+    }
+
+    #[derive(CustomDebug, CustomDisplay)]
+    #[debug(skip_none)]
+    #[display("{name}")]
+    pub struct Sensor<T> {
+        pub name: &'static str,
+        #[debug = "{:#x}"]
+        pub id: u16,
+        #[debug(with = "f")]
+        pub status: u8,
+        pub reading: ::core::option::Option<T>,
+        #[debug(redact_len)]
+        pub key: &'static str,
+    }
+
+    #[derive(CustomDebug, CustomDisplay)]
+    pub enum State {
+        #[display("idle")]
+        Idle,
+        #[display("busy for {0}ms")]
+        Busy(u32),
+    }
+
+    #[derive(CustomDebug)]
+    #[debug(transparent)]
+    pub struct Id(pub u32);
+}
+
+fn main() {
+    let sensor = no_prelude::Sensor::<i8> {
+        name: "thermo",
+        id: 0x2a,
+        status: 0,
+        reading: None,
+        key: "secret",
+    };
+    assert_eq!(
+        format!("{:?}", sensor),
+        r#"Sensor { name: "thermo", id: 0x2a, status: zero, key: <6 bytes> }"#,
+    );
+    assert_eq!(format!("{}", sensor), "thermo");
+    assert_eq!(format!("{:?}", no_prelude::State::Busy(5)), "Busy(5)");
+    assert_eq!(format!("{}", no_prelude::State::Busy(5)), "busy for 5ms");
+    assert_eq!(format!("{:?}", no_prelude::Id(3)), "3");
+}
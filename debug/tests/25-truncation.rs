@@ -0,0 +1,74 @@
+// Large fields can be cut short so that one value can't flood a log line:
+//
+//   - #[debug(max_items = N)] shows at most N items of a sequence, followed by
+//     how many were left out, like `[1, 2, 3, … (1021 more)]`.
+//   - #[debug(max_len = N)] shows at most N characters of a `String` or `str`,
+//     or N items of a byte buffer.
+//
+// Fields short enough are shown unchanged. Sequences work through their
+// `iter()` method, so any standard collection can be truncated.
+
+use derive_debug::CustomDebug;
+use std::collections::BTreeSet;
+
+#[derive(CustomDebug)]
+pub struct Upload<'a, T> {
+    #[debug(max_items = 3)]
+    numbers: Vec<u32>,
+    #[debug(max_items = 2)]
+    tags: BTreeSet<T>,
+    #[debug(max_len = 5)]
+    name: String,
+    #[debug(max_len = 5)]
+    note: &'a str,
+    #[debug(max_len = 2)]
+    payload: &'a [u8],
+    #[debug(max_items = 4)]
+    short: [u8; 2],
+}
+
+fn main() {
+    let payload = vec![0; 1 << 20];
+    let upload = Upload {
+        numbers: (1..=1024).collect(),
+        tags: ["c", "b", "a"].into_iter().collect(),
+        name: "héllo world".to_owned(),
+        note: "short",
+        payload: &payload,
+        short: [7, 8],
+    };
+    assert_eq!(
+        format!("{:?}", upload),
+        concat!(
+            "Upload { ",
+            "numbers: [1, 2, 3, … (1021 more)], ",
+            r#"tags: ["a", "b", … (1 more)], "#,
+            r#"name: "héllo"… (6 more), "#,
+            r#"note: "short", "#,
+            "payload: [0, 0, … (1048574 more)], ",
+            "short: [7, 8] }",
+        ),
+    );
+    assert_eq!(
+        format!("{:#?}", Upload::<u8> {
+            numbers: vec![1, 2, 3, 4],
+            tags: BTreeSet::new(),
+            name: String::new(),
+            note: "",
+            payload: &[],
+            short: [0, 0],
+        })
+        .lines()
+        .take(7)
+        .collect::<Vec<_>>(),
+        [
+            "Upload {",
+            "    numbers: [",
+            "        1,",
+            "        2,",
+            "        3,",
+            "        … (1 more),",
+            "    ],",
+        ],
+    );
+}
@@ -0,0 +1,87 @@
+// Shortcuts for common format strings: #[debug(hex)], #[debug(binary)],
+// #[debug(octal)] and #[debug(ptr)] format a value like `{:#x}`, `{:#b}`,
+// `{:#o}` and `{:p}`.
+//
+// On arrays, slices and `Vec`s they apply to each element instead, except
+// that a sequence of `u8` in hex is shown as a single string of digits, which
+// is how hashes and keys are usually written.
+//
+// The bounds inferred for these fields use the matching formatting trait, so
+// `T: LowerHex` rather than `T: Debug`.
+
+use derive_debug::CustomDebug;
+use std::fmt::LowerHex;
+
+#[derive(CustomDebug)]
+pub struct Packet<'a, T> {
+    #[debug(hex)]
+    flags: u16,
+    #[debug(binary)]
+    mask: u8,
+    #[debug(octal)]
+    mode: u32,
+    #[debug(hex)]
+    hash: [u8; 8],
+    #[debug(hex)]
+    payload: &'a [u8],
+    #[debug(hex)]
+    words: Vec<u32>,
+    #[debug(binary)]
+    bits: [u8; 2],
+    #[debug(hex)]
+    generic: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Pointers<'a> {
+    #[debug(ptr)]
+    target: &'a u8,
+    #[debug(ptr)]
+    targets: Vec<&'a u8>,
+}
+
+pub struct NotDebug(u8);
+
+impl LowerHex for NotDebug {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        LowerHex::fmt(&self.0, f)
+    }
+}
+
+fn main() {
+    let packet = Packet {
+        flags: 0x1f,
+        mask: 5,
+        mode: 0o755,
+        hash: [0xde, 0xad, 0xbe, 0xef, 0, 1, 2, 3],
+        payload: &[0x0a, 0xff],
+        words: vec![1, 0xffff],
+        bits: [1, 2],
+        generic: NotDebug(0xab),
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        concat!(
+            "Packet { ",
+            "flags: 0x1f, ",
+            "mask: 0b101, ",
+            "mode: 0o755, ",
+            "hash: deadbeef00010203, ",
+            "payload: 0aff, ",
+            "words: [0x1, 0xffff], ",
+            "bits: [0b1, 0b10], ",
+            "generic: 0xab }",
+        ),
+    );
+
+    let value = 1;
+    let pointers = Pointers {
+        target: &value,
+        targets: vec![&value],
+    };
+    let address = format!("{:p}", &value);
+    assert_eq!(
+        format!("{:?}", pointers),
+        format!("Pointers {{ target: {0}, targets: [{0}] }}", address),
+    );
+}
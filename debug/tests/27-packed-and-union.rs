@@ -0,0 +1,96 @@
+// Fields of a #[repr(packed)] struct may be unaligned, so they can't be
+// borrowed in place. The derive copies each shown field out before formatting
+// it, which works for any packed struct whose shown fields are `Copy`.
+// CustomDisplay does the same for the fields its template uses.
+//
+// Unions don't know which of their fields is active, so by default none of
+// them is read and the union is shown as `Name { .. }`. A type-level
+// #[debug(with = "...")] shows the whole value with a function instead, which
+// also works for structs and enums.
+
+use derive_debug::{CustomDebug, CustomDisplay};
+use std::fmt;
+
+#[derive(CustomDebug)]
+#[repr(C, packed)]
+pub struct Header {
+    tag: u8,
+    #[debug(hex)]
+    len: u32,
+    #[debug(skip)]
+    checksum: u16,
+}
+
+#[derive(CustomDebug)]
+#[repr(packed(2))]
+pub struct Pair<T: Copy>(u8, T);
+
+#[derive(CustomDisplay)]
+#[repr(packed)]
+#[display("{major}.{minor:02}")]
+pub struct Version {
+    major: u8,
+    minor: u16,
+    notes: [u8; 3],
+}
+
+#[derive(CustomDisplay)]
+#[repr(packed(2))]
+#[display("{0}:{1}")]
+pub struct Tagged<T: Copy>(u8, T);
+
+#[derive(CustomDebug)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+#[derive(CustomDebug)]
+#[debug(with = "debug_word")]
+pub union Word {
+    int: u32,
+    bytes: [u8; 4],
+}
+
+fn debug_word(word: &Word, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Word({:#x})", unsafe { word.int })
+}
+
+#[derive(CustomDebug)]
+#[debug(with = "Celsius::show")]
+pub struct Celsius(f64);
+
+impl Celsius {
+    fn show(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}°C", self.0)
+    }
+}
+
+fn main() {
+    let header = Header {
+        tag: 7,
+        len: 300,
+        checksum: 0xbeef,
+    };
+    assert_eq!(
+        format!("{:?}", header),
+        "Header { tag: 7, len: 0x12c, .. }"
+    );
+    let _ = header.checksum;
+
+    assert_eq!(format!("{:?}", Pair(1, 2u64)), "Pair(1, 2)");
+
+    let version = Version {
+        major: 1,
+        minor: 7,
+        notes: [0; 3],
+    };
+    assert_eq!(version.to_string(), "1.07");
+    let _ = version.notes;
+    assert_eq!(Tagged(3, 400u32).to_string(), "3:400");
+
+    assert_eq!(format!("{:?}", Bits { int: 1 }), "Bits { .. }");
+    assert_eq!(format!("{:?}", Word { int: 255 }), "Word(0xff)");
+    assert_eq!(format!("{:?}", Word { bytes: [0; 4] }), "Word(0x0)");
+    assert_eq!(format!("{:?}", Celsius(21.5)), "21.5°C");
+}
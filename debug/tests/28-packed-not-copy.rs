@@ -0,0 +1,16 @@
+// Fields of a packed struct are copied out before being shown, so each field
+// that is shown has to be `Copy`. The error points at the offending field's
+// type. Skipping the field avoids the requirement.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[repr(packed)]
+pub struct Message {
+    id: u32,
+    body: String,
+    #[debug(skip)]
+    extra: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,93 @@
+// Misused #[debug] attributes are errors reported at the offending tokens,
+// never silently ignored:
+//
+//   - An unknown key names the closest known one when it looks like a typo,
+//     and lists the accepted options otherwise.
+//   - A known key written in the wrong form lists the accepted forms.
+//   - A field can't be both skipped and shown through a function.
+//   - Only strings and byte buffers have a length in bytes to show.
+//   - A format string may format only the field itself, so widths and
+//     precisions taken from other arguments are rejected.
+//   - A bound must parse as where predicates.
+//   - Types shown as a whole, through `display` or `with`, and unions have no
+//     field by field output for inner attributes to change.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparant)]
+pub struct TypoOnType(u8);
+
+#[derive(CustomDebug)]
+pub enum Variant {
+    #[debug(nmae = "B")]
+    A,
+}
+
+#[derive(CustomDebug)]
+pub struct TypoOnField {
+    #[debug(skp)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct UnknownKey {
+    #[debug(colour = "red")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct WrongForm {
+    #[debug(skip = "yes")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct SkipAndWith {
+    #[debug(skip, with = "show")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct LengthOfWords {
+    #[debug(redact_len)]
+    words: Vec<u32>,
+}
+
+#[derive(CustomDebug)]
+pub struct WidthArgument {
+    #[debug = "{:width$}"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct BadSpec {
+    #[debug = "{:q}"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct SecondArgument {
+    #[debug = "{1}"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "where T: Debug")]
+pub struct BadBound<T>(T);
+
+#[derive(CustomDebug)]
+#[debug(display)]
+pub struct Shown {
+    #[debug(skip)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub union Bits {
+    #[debug(hex)]
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
@@ -0,0 +1,27 @@
+// Any character can fill the padding of a format spec, including `*` and `$`,
+// which elsewhere in a spec would take the width or precision from another
+// argument.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Padded {
+    #[debug = "{:*^8}"]
+    center: u8,
+    #[debug = "{:*<6}"]
+    left: u8,
+    #[debug = "{:$>5}"]
+    right: u8,
+}
+
+fn main() {
+    let padded = Padded {
+        center: 1,
+        left: 22,
+        right: 3,
+    };
+    assert_eq!(
+        format!("{:?}", padded),
+        "Padded { center: ***1****, left: 22****, right: $$$$3 }"
+    );
+}
@@ -0,0 +1,42 @@
+// A field with #[debug = "..."] is bounded by the formatting traits its
+// placeholders use rather than always by Debug, so `{:x}` needs `T: LowerHex`
+// and `{}` needs `U: Display`. A field used by several placeholders is
+// bounded by each of their traits.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Display};
+
+#[derive(CustomDebug)]
+pub struct Register<T, U> {
+    #[debug = "{:#x}"]
+    value: T,
+    #[debug = "{}"]
+    label: U,
+    #[debug = "{0} ({0:#b})"]
+    mask: T,
+}
+
+// Implements Display but not Debug.
+pub struct Label(&'static str);
+
+impl Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+fn assert_debug<F: fmt::Debug>() {}
+
+fn main() {
+    assert_debug::<Register<u8, Label>>();
+
+    let register = Register {
+        value: 255u8,
+        label: Label("status"),
+        mask: 5,
+    };
+    assert_eq!(
+        format!("{:?}", register),
+        "Register { value: 0xff, label: status, mask: 5 (0b101) }"
+    );
+}
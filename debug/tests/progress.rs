@@ -3,10 +3,33 @@ fn tests() {
     let t = trybuild::TestCases::new();
     t.pass("tests/01-parse.rs");
     t.pass("tests/02-impl-debug.rs");
-    //t.pass("tests/03-custom-format.rs");
-    //t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/03-custom-format.rs");
+    t.pass("tests/04-type-parameter.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-inferred-bounds.rs");
+    t.compile_fail("tests/10-bad-format-string.rs");
+    t.pass("tests/11-field-bound.rs");
+    t.compile_fail("tests/12-bad-bound.rs");
+    t.pass("tests/13-enums.rs");
+    t.pass("tests/14-skip-and-redact.rs");
+    t.compile_fail("tests/15-conflicting-field-attributes.rs");
+    t.pass("tests/16-with-function.rs");
+    t.pass("tests/17-rename.rs");
+    t.pass("tests/18-transparent-and-display.rs");
+    t.compile_fail("tests/19-transparent-multiple-fields.rs");
+    t.pass("tests/20-skip-none-and-skip-if.rs");
+    t.pass("tests/21-custom-display.rs");
+    t.compile_fail("tests/22-display-unknown-field.rs");
+    t.pass("tests/23-bound-traversal.rs");
+    t.pass("tests/24-no-std.rs");
+    t.pass("tests/25-truncation.rs");
+    t.pass("tests/26-radix-presets.rs");
+    t.pass("tests/27-packed-and-union.rs");
+    t.compile_fail("tests/28-packed-not-copy.rs");
+    t.compile_fail("tests/29-misuse-diagnostics.rs");
+    t.pass("tests/30-format-fill.rs");
+    t.pass("tests/31-format-trait-bounds.rs");
 }